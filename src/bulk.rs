@@ -0,0 +1,273 @@
+//! Bulk property entry from pasted CSV or TSV rows
+
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{default_additional_properties, DataType, Model, Msg, Property};
+
+/// Column order when the rows have no header
+const COLUMNS: [&str; 9] = ["name", "type", "required", "minLength", "maxLength", "minimum", "maximum", "pattern", "description"];
+
+/// Data types as written in the type column, matched case-insensitively
+const DATA_TYPES: [&str; 6] = ["String", "Integer", "Array", "Object", "Number", "Boolean"];
+
+/// A pasted row, parsed into a property or the reasons it couldn't be
+pub struct BulkRow {
+    /// Line number in the pasted text
+    pub line: usize,
+    pub result: Result<Property, Vec<String>>,
+}
+
+/// Splits a line into fields. Fields may be double-quoted, with `""` for a quote inside them.
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "" | "false" | "no" | "n" | "0" => Some(false),
+        "true" | "yes" | "y" | "1" | "x" => Some(true),
+        _ => None,
+    }
+}
+
+/// Parses an optional number column, recording an error if it isn't a number
+fn parse_number<T: std::str::FromStr>(value: &str, column: &str, errors: &mut Vec<String>) -> Option<T> {
+    if value.is_empty() {
+        return None;
+    }
+    let number = value.parse().ok();
+    if number.is_none() {
+        errors.push(format!("{} \"{}\" is not a valid number", column, value));
+    }
+    number
+}
+
+/// Parses one row whose fields are keyed by column name
+fn parse_row(field: impl Fn(&str) -> String, taken: &[String]) -> Result<Property, Vec<String>> {
+    let mut errors = vec![];
+    let name = field("name");
+    if name.is_empty() {
+        errors.push(String::from("The name is empty"));
+    } else if taken.contains(&name) {
+        errors.push(format!("A property named \"{}\" already exists", name));
+    }
+    let type_name = field("type");
+    let data_type = if type_name.is_empty() {
+        Some("String")
+    } else {
+        DATA_TYPES.iter().copied().find(|t| t.eq_ignore_ascii_case(&type_name))
+    };
+    if data_type.is_none() {
+        errors.push(format!("Unknown type \"{}\". Use one of {}", type_name, DATA_TYPES.join(", ")));
+    }
+    let required = parse_bool(&field("required")).unwrap_or_else(|| {
+        errors.push(format!("required \"{}\" should be true or false", field("required")));
+        false
+    });
+    let min_length = parse_number::<u32>(&field("minLength"), "minLength", &mut errors);
+    let max_length = parse_number::<u32>(&field("maxLength"), "maxLength", &mut errors);
+    let minimum = parse_number::<i32>(&field("minimum"), "minimum", &mut errors);
+    let maximum = parse_number::<i32>(&field("maximum"), "maximum", &mut errors);
+    let pattern = Some(field("pattern")).filter(|p| !p.is_empty());
+    let description = Some(field("description")).filter(|d| !d.is_empty());
+
+    let mut property = default_additional_properties(data_type.unwrap_or("String"));
+    if data_type.is_some() {
+        let is_string = property.data_type == DataType::String;
+        let is_numeric = matches!(property.data_type, DataType::Integer | DataType::Number);
+        if !is_string && (min_length.is_some() || max_length.is_some() || pattern.is_some()) {
+            errors.push(String::from("minLength, maxLength and pattern only apply to string properties"));
+        }
+        if !is_numeric && (minimum.is_some() || maximum.is_some()) {
+            errors.push(String::from("minimum and maximum only apply to integer and number properties"));
+        }
+    }
+    if let (Some(min), Some(max)) = (min_length, max_length) {
+        if min > max {
+            errors.push(String::from("minLength is greater than maxLength"));
+        }
+    }
+    if let (Some(min), Some(max)) = (minimum, maximum) {
+        if min > max {
+            errors.push(String::from("minimum is greater than maximum"));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    property.name = name;
+    property.required = required;
+    property.min_length = min_length;
+    property.max_length = max_length;
+    property.minimum = minimum;
+    property.maximum = maximum;
+    property.pattern = pattern;
+    property.description = description;
+    Ok(property)
+}
+
+/// Parses pasted rows into properties. Tab-separated input is read as TSV, anything else as CSV.
+/// A first row starting with `name` is a header and may list the columns in any order.
+/// Names must be unique among the new rows and the `existing` properties.
+pub fn parse_rows(input: &str, existing: &[String]) -> Result<Vec<BulkRow>, String> {
+    let delimiter = if input.contains('\t') { '\t' } else { ',' };
+    let mut lines = input.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).peekable();
+    let mut columns = COLUMNS.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    if let Some((_, first)) = lines.peek() {
+        let header = split_fields(first, delimiter);
+        if header.first().map_or(false, |f| f.eq_ignore_ascii_case("name")) {
+            columns = vec![];
+            for field in header {
+                match COLUMNS.iter().find(|c| c.eq_ignore_ascii_case(&field)) {
+                    Some(column) => columns.push(column.to_string()),
+                    None => return Err(format!("Unknown column \"{}\". Columns are {}", field, COLUMNS.join(", "))),
+                }
+            }
+            lines.next();
+        }
+    }
+    let mut taken = existing.to_vec();
+    let mut rows = vec![];
+    for (i, line) in lines {
+        let fields = split_fields(line, delimiter);
+        let result = if fields.len() > columns.len() {
+            Err(vec![format!("{} fields, but there are only {} columns", fields.len(), columns.len())])
+        } else {
+            let field = |column: &str| columns.iter().position(|c| c == column).and_then(|p| fields.get(p)).cloned().unwrap_or_default();
+            parse_row(field, &taken)
+        };
+        if let Ok(property) = &result {
+            taken.push(property.name.clone());
+        }
+        rows.push(BulkRow { line: i + 1, result });
+    }
+    Ok(rows)
+}
+
+impl Model {
+    fn property_names(&self, doc_index: usize) -> Vec<String> {
+        self.document_types[doc_index].properties.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// Adds the pasted properties to the document type if every row is valid
+    pub fn apply_bulk_entry(&mut self) {
+        let doc_index = match self.bulk_document_type.filter(|i| *i < self.document_types.len()) {
+            Some(doc_index) => doc_index,
+            None => return,
+        };
+        let rows = parse_rows(&self.bulk_input, &self.property_names(doc_index)).unwrap_or_default();
+        let properties = rows.into_iter().map(|row| row.result).collect::<Result<Vec<_>, _>>().unwrap_or_default();
+        if !properties.is_empty() {
+            let document_type = &mut self.document_types[doc_index];
+            // Drop the empty property a new document type starts with
+            if document_type.properties.len() == 1 && document_type.properties[0] == Property::default() {
+                document_type.properties.clear();
+            }
+            document_type.properties.extend(properties);
+            self.bulk_document_type = None;
+            self.bulk_input = String::new();
+        }
+    }
+
+    /// Paste area for a document type's bulk entry, with the parsed rows and their errors
+    pub fn view_bulk_entry(&self, ctx: &yew::Context<Self>, doc_index: usize) -> Html {
+        if self.bulk_document_type != Some(doc_index) {
+            return html! {
+                <button class="button2" onclick={ctx.link().callback(move |_| Msg::OpenBulkEntry(doc_index))}>{"Bulk add properties"}</button>
+            };
+        }
+        let parsed = parse_rows(&self.bulk_input, &self.property_names(doc_index));
+        let rows = parsed.as_ref().map(|rows| rows.as_slice()).unwrap_or_default();
+        let valid = rows.iter().filter(|row| row.result.is_ok()).count();
+        let can_apply = parsed.is_ok() && valid > 0 && valid == rows.len();
+        html! {
+            <div class="bulk-entry">
+                <p>{format!("Paste CSV or TSV rows of {}. A header row may list the columns in another order.", COLUMNS.join(", "))}</p>
+                <textarea class="textarea" placeholder={"name,type,required,minLength,maxLength,minimum,maximum,pattern,description\nlabel,String,true,3,63,,,,Domain label"} value={self.bulk_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateBulkInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <ul class="error-text">
+                    { for parsed.as_ref().err().map(|e| html! { <li>{e.clone()}</li> }) }
+                    { for rows.iter().filter_map(|row| row.result.as_ref().err().map(|errors| html! {
+                        <li>{format!("Line {}: {}", row.line, errors.join("; "))}</li>
+                    })) }
+                </ul>
+                {
+                    if valid > 0 {
+                        html! { <p>{format!("{} of {} rows are ready to add", valid, rows.len())}</p> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <div>
+                    <button class="button2" disabled={!can_apply} onclick={ctx.link().callback(|_| Msg::ApplyBulkEntry)}>{"Add properties"}</button>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::CloseBulkEntry)}>{"Cancel"}</button>
+                </div>
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_fields_handles_quotes() {
+        assert_eq!(split_fields(r#"name, "a, b" ,"say ""hi""",,x"#, ','), ["name", "a, b", "say \"hi\"", "", "x"]);
+        assert_eq!(split_fields("a\t b\t", '\t'), ["a", "b", ""]);
+    }
+
+    #[test]
+    fn parse_rows_reads_csv_in_column_order() {
+        let rows = parse_rows("title,string,yes,1,100,,,^[a-z]+$,\"The title, in lowercase\"\ncount,Integer,,,,0,10", &[]).unwrap();
+        let title = rows[0].result.as_ref().unwrap();
+        assert_eq!((rows[0].line, title.name.as_str(), &title.data_type, title.required), (1, "title", &DataType::String, true));
+        assert_eq!((title.min_length, title.max_length), (Some(1), Some(100)));
+        assert_eq!(title.pattern.as_deref(), Some("^[a-z]+$"));
+        assert_eq!(title.description.as_deref(), Some("The title, in lowercase"));
+        let count = rows[1].result.as_ref().unwrap();
+        assert_eq!((rows[1].line, &count.data_type, count.required, count.minimum, count.maximum), (2, &DataType::Integer, false, Some(0), Some(10)));
+    }
+
+    #[test]
+    fn parse_rows_reads_a_tsv_header_in_any_order() {
+        let rows = parse_rows("name\tkind\ndone\tboolean\n", &[]);
+        assert_eq!(rows.err().unwrap(), "Unknown column \"kind\". Columns are name, type, required, minLength, maxLength, minimum, maximum, pattern, description");
+        let rows = parse_rows("name\tTYPE\tmaximum\n\ndone\tboolean\t\nscore\tnumber\t5\n", &[]).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].result.as_ref().unwrap().data_type, DataType::Boolean);
+        assert_eq!((rows[1].line, rows[1].result.as_ref().unwrap().maximum), (4, Some(5)));
+    }
+
+    #[test]
+    fn parse_rows_reports_invalid_rows() {
+        let errors = |input: &str| parse_rows(input, &[String::from("taken")]).unwrap().remove(0).result.unwrap_err();
+        assert_eq!(errors("taken"), ["A property named \"taken\" already exists"]);
+        assert_eq!(errors(",date,maybe"), ["The name is empty", "Unknown type \"date\". Use one of String, Integer, Array, Object, Number, Boolean", "required \"maybe\" should be true or false"]);
+        assert_eq!(errors("a,integer,,x,,5,1"), ["minLength \"x\" is not a valid number", "minimum is greater than maximum"]);
+        assert_eq!(errors("a,boolean,,1"), ["minLength, maxLength and pattern only apply to string properties"]);
+        assert_eq!(errors("a,string,,,,,,,,extra"), ["10 fields, but there are only 9 columns"]);
+        let rows = parse_rows("a\na", &[]).unwrap();
+        assert!(rows[0].result.is_ok());
+        assert_eq!(rows[1].result.as_ref().unwrap_err(), &["A property named \"a\" already exists"]);
+    }
+}
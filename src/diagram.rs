@@ -0,0 +1,304 @@
+//! Entity-relationship diagram of the document types as SVG
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, TargetCast};
+
+use crate::{files::Download, is_identifier, DataType, DocumentType, Model, Msg, Property};
+
+/// An identifier property marked as pointing to documents of another document type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reference {
+    pub document_type: String,
+    /// Property name, dotted for nested properties
+    pub property: String,
+    /// Name of the referenced document type
+    pub target: String,
+}
+
+const NODE_MIN_WIDTH: f64 = 200.0;
+const HEADER_HEIGHT: f64 = 28.0;
+const ROW_HEIGHT: f64 = 18.0;
+/// Width of a character in the 12px monospace font
+const CHAR_WIDTH: f64 = 7.3;
+const GAP: f64 = 90.0;
+const MARGIN: f64 = 20.0;
+
+fn type_name(property: &Property) -> &'static str {
+    match property.data_type {
+        DataType::String => "string",
+        DataType::Integer => "integer",
+        DataType::Array if property.byte_array == Some(true) => "bytes",
+        DataType::Array => "array",
+        DataType::Object => "object",
+        DataType::Number => "number",
+        DataType::Boolean => "boolean",
+    }
+}
+
+/// Properties with dotted names for nested ones, in form order
+fn flat_properties(document_type: &DocumentType) -> Vec<(String, &Property)> {
+    let mut properties = vec![];
+    for property in &document_type.properties {
+        properties.push((property.name.clone(), property));
+        for nested in property.properties.iter().flat_map(|p| p.iter()) {
+            properties.push((format!("{}.{}", property.name, nested.name), nested));
+        }
+    }
+    properties
+}
+
+/// Identifier properties of every document schema, which can be marked as references. Names are
+/// dotted for nested properties.
+pub fn identifier_properties(documents: &Value) -> Vec<(String, String)> {
+    let mut identifiers = vec![];
+    for (document_type, schema) in documents.as_object().into_iter().flatten() {
+        for (name, property) in schema.get("properties").and_then(|p| p.as_object()).into_iter().flatten() {
+            if is_identifier(property) {
+                identifiers.push((document_type.clone(), name.clone()));
+            }
+            for (nested_name, nested) in property.get("properties").and_then(|p| p.as_object()).into_iter().flatten() {
+                if is_identifier(nested) {
+                    identifiers.push((document_type.clone(), format!("{}.{}", name, nested_name)));
+                }
+            }
+        }
+    }
+    identifiers
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// A document type's box in the diagram
+struct Node {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    /// Property rows, with the property name each row is for
+    rows: Vec<(Option<String>, String)>,
+}
+
+impl Node {
+    /// Vertical middle of a row
+    fn row_y(&self, row: usize) -> f64 {
+        self.y + HEADER_HEIGHT + ROW_HEIGHT * (row as f64 + 0.5) + 4.0
+    }
+}
+
+fn layout(document_types: &[DocumentType], references: &[Reference]) -> Vec<Node> {
+    let columns = (document_types.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let mut nodes = document_types
+        .iter()
+        .map(|document_type| {
+            let mut rows = flat_properties(document_type)
+                .into_iter()
+                .map(|(name, property)| {
+                    let target = references.iter().find(|r| r.document_type == document_type.name && r.property == name).map(|r| format!(" → {}", r.target));
+                    let text = format!("{}{}: {}{}", name, if property.required { "*" } else { "" }, type_name(property), target.unwrap_or_default());
+                    (Some(name), text)
+                })
+                .collect::<Vec<_>>();
+            if !document_type.indices.is_empty() {
+                rows.push((None, String::from("Indices")));
+                for index in &document_type.indices {
+                    let properties = index.properties.iter().map(|p| format!("{} {}", p.0, p.1)).collect::<Vec<_>>().join(", ");
+                    rows.push((None, format!("{}{}: {}", index.name, if index.unique { " (unique)" } else { "" }, properties)));
+                }
+            }
+            let longest = rows.iter().map(|(_, text)| text.chars().count()).chain(Some(document_type.name.chars().count())).max().unwrap_or(0);
+            Node {
+                x: 0.0,
+                y: 0.0,
+                width: (longest as f64 * CHAR_WIDTH + 20.0).max(NODE_MIN_WIDTH),
+                height: HEADER_HEIGHT + ROW_HEIGHT * rows.len() as f64 + 8.0,
+                rows,
+            }
+        })
+        .collect::<Vec<_>>();
+    // Grid layout: each column is as wide as its widest node, each row as tall as its tallest
+    let column_widths = (0..columns).map(|c| nodes.iter().skip(c).step_by(columns).map(|n| n.width).fold(0.0, f64::max)).collect::<Vec<_>>();
+    let mut y = MARGIN;
+    for row in nodes.chunks_mut(columns) {
+        let mut x = MARGIN;
+        let height = row.iter().map(|n| n.height).fold(0.0, f64::max);
+        for (c, node) in row.iter_mut().enumerate() {
+            node.x = x;
+            node.y = y;
+            x += column_widths[c] + GAP;
+        }
+        y += height + GAP;
+    }
+    nodes
+}
+
+/// The diagram as a standalone SVG document. References whose property or target no longer
+/// exists are left out.
+pub fn generate_svg(document_types: &[DocumentType], references: &[Reference]) -> String {
+    let nodes = layout(document_types, references);
+    let width = nodes.iter().map(|n| n.x + n.width).fold(0.0, f64::max) + MARGIN;
+    let height = nodes.iter().map(|n| n.y + n.height).fold(0.0, f64::max) + MARGIN;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"12\">\n",
+        w = width.ceil(),
+        h = height.ceil()
+    );
+    svg.push_str("<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#c2185b\"/></marker></defs>\n");
+    svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n", width.ceil(), height.ceil()));
+
+    for reference in references {
+        let source = document_types.iter().position(|d| d.name == reference.document_type);
+        let target = document_types.iter().position(|d| d.name == reference.target);
+        let (source, target) = match (source, target) {
+            (Some(source), Some(target)) => (source, target),
+            _ => continue,
+        };
+        let row = match nodes[source].rows.iter().position(|(name, _)| name.as_deref() == Some(reference.property.as_str())) {
+            Some(row) => row,
+            None => continue,
+        };
+        let (from, to) = (&nodes[source], &nodes[target]);
+        let y1 = from.row_y(row);
+        let y2 = to.y + HEADER_HEIGHT / 2.0;
+        // Leave from the side facing the target and enter its header from the near side.
+        // References within a column loop around the right side.
+        let path = if to.x > from.x + from.width {
+            let (x1, x2) = (from.x + from.width, to.x);
+            format!("M {} {} C {} {}, {} {}, {} {}", x1, y1, x1 + 40.0, y1, x2 - 40.0, y2, x2, y2)
+        } else if to.x + to.width < from.x {
+            let (x1, x2) = (from.x, to.x + to.width);
+            format!("M {} {} C {} {}, {} {}, {} {}", x1, y1, x1 - 40.0, y1, x2 + 40.0, y2, x2, y2)
+        } else {
+            let (x1, x2) = (from.x + from.width, to.x + to.width);
+            let bend = x1.max(x2) + 40.0;
+            format!("M {} {} C {} {}, {} {}, {} {}", x1, y1, bend, y1, bend, y2, x2, y2)
+        };
+        svg.push_str(&format!("<path d=\"{}\" fill=\"none\" stroke=\"#c2185b\" stroke-width=\"1.5\" marker-end=\"url(#arrow)\"/>\n", path));
+    }
+
+    for (document_type, node) in document_types.iter().zip(&nodes) {
+        svg.push_str(&format!("<g transform=\"translate({} {})\">\n", node.x, node.y));
+        svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" rx=\"5\" fill=\"#ffffff\" stroke=\"#008de4\" stroke-width=\"1.5\"/>\n", node.width, node.height));
+        svg.push_str(&format!("<path d=\"M 0 {h} V 5 Q 0 0 5 0 H {w5} Q {w} 0 {w} 5 V {h} Z\" fill=\"#008de4\"/>\n", h = HEADER_HEIGHT, w = node.width, w5 = node.width - 5.0));
+        let name = if document_type.name.is_empty() { "(unnamed)" } else { &document_type.name };
+        svg.push_str(&format!("<text x=\"10\" y=\"19\" fill=\"#ffffff\" font-weight=\"bold\">{}</text>\n", escape(name)));
+        for (i, (property, text)) in node.rows.iter().enumerate() {
+            let y = node.row_y(i) - node.y + 4.0;
+            let style = if property.is_some() { "fill=\"#222222\"" } else if text == "Indices" { "fill=\"#008de4\" font-weight=\"bold\"" } else { "fill=\"#666666\"" };
+            svg.push_str(&format!("<text x=\"10\" y=\"{}\" {}>{}</text>\n", y, style, escape(text)));
+        }
+        svg.push_str("</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The new dotted name of `property` after `old` was renamed to `new`, if it is `old` or nested in it
+fn renamed_property(property: &str, old: &str, new: &str) -> Option<String> {
+    if property == old {
+        Some(new.to_string())
+    } else {
+        property.strip_prefix(old).filter(|rest| rest.starts_with('.')).map(|rest| format!("{}{}", new, rest))
+    }
+}
+
+impl Model {
+    /// Points the references from and to a renamed document type at its new name
+    pub fn rename_document_type_references(&mut self, old: &str, new: &str) {
+        for reference in &mut self.references {
+            if reference.document_type == old {
+                reference.document_type = new.to_string();
+            }
+            if reference.target == old {
+                reference.target = new.to_string();
+            }
+        }
+    }
+
+    /// Moves the references of a renamed property, and of the properties nested in it, to its new
+    /// name. Names are dotted for nested properties.
+    pub fn rename_property_references(&mut self, document_type: &str, old: &str, new: &str) {
+        for reference in self.references.iter_mut().filter(|r| r.document_type == document_type) {
+            if let Some(property) = renamed_property(&reference.property, old, new) {
+                reference.property = property;
+            }
+        }
+    }
+
+    /// Gives a copied document type the references of the original
+    pub fn copy_document_type_references(&mut self, original: &str, copy: &str) {
+        let copies = self.references.iter()
+            .filter(|r| r.document_type == original)
+            .map(|r| Reference { document_type: copy.to_string(), ..r.clone() })
+            .collect::<Vec<_>>();
+        self.references.extend(copies);
+    }
+
+    /// Gives a copied property, and the properties nested in it, the references of the original
+    pub fn copy_property_references(&mut self, document_type: &str, original: &str, copy: &str) {
+        let copies = self.references.iter()
+            .filter(|r| r.document_type == document_type)
+            .filter_map(|r| renamed_property(&r.property, original, copy).map(|property| Reference { property, ..r.clone() }))
+            .collect::<Vec<_>>();
+        self.references.extend(copies);
+    }
+
+    /// Marks an identifier property as a reference to `target`, or unmarks it if `target` is empty
+    pub fn update_reference(&mut self, document_type: String, property: String, target: String) {
+        self.references.retain(|r| !(r.document_type == document_type && r.property == property));
+        if !target.is_empty() {
+            self.references.push(Reference { document_type, property, target });
+        }
+    }
+
+    pub fn diagram_svg(&self) -> String {
+        generate_svg(&self.document_types, &self.references)
+    }
+
+    pub fn view_diagram(&self, ctx: &yew::Context<Self>) -> Html {
+        let identifiers = identifier_properties(&self.documents_json());
+        let src = format!("data:image/svg+xml;base64,{}", STANDARD.encode(self.diagram_svg()));
+        html! {
+            <p class="output-container">
+                <h2>{"Diagram"}</h2>
+                <h3>{"References"}</h3>
+                {
+                    if identifiers.is_empty() {
+                        html! { <p>{"Identifier properties (byte arrays with the identifier content media type) can be marked as references to another document type."}</p> }
+                    } else {
+                        html! {
+                            <table>
+                                <tbody>
+                                    {for identifiers.into_iter().map(|(document_type, property)| {
+                                        let target = self.references.iter().find(|r| r.document_type == document_type && r.property == property).map(|r| r.target.clone()).unwrap_or_default();
+                                        let label = format!("{}.{}", document_type, property);
+                                        html! {
+                                            <tr>
+                                                <td>{label}</td>
+                                                <td>{"→"}</td>
+                                                <td>
+                                                    <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateReference(document_type.clone(), property.clone(), e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                                                        <option value="" selected={target.is_empty()}>{"No reference"}</option>
+                                                        {for self.document_types.iter().map(|d| html! {
+                                                            <option value={d.name.clone()} selected={d.name == target}>{d.name.clone()}</option>
+                                                        })}
+                                                    </select>
+                                                </td>
+                                            </tr>
+                                        }
+                                    })}
+                                </tbody>
+                            </table>
+                        }
+                    }
+                }
+                <div class="diagram"><img src={src} alt="Entity-relationship diagram of the document types" /></div>
+                <div><button class="button2" onclick={ctx.link().callback(|_| Msg::Download(Download::Diagram))}>{"Download SVG"}</button></div>
+            </p>
+        }
+    }
+}
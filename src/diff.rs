@@ -0,0 +1,463 @@
+//! Structural comparison of document schemas and their backward compatibility
+
+use serde_json::{json, Map, Value};
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{files::Download, Model, Msg};
+
+/// Keywords that only document a schema and never affect which documents are valid
+const ANNOTATION_KEYWORDS: [&str; 2] = ["description", "$comment"];
+
+/// Keywords whose value is a lower bound, so raising or adding them tightens the schema
+const LOWER_BOUNDS: [&str; 4] = ["minLength", "minimum", "minItems", "minProperties"];
+
+/// Keywords whose value is an upper bound, so lowering or adding them tightens the schema
+const UPPER_BOUNDS: [&str; 4] = ["maxLength", "maximum", "maxItems", "maxProperties"];
+
+/// Keywords that are compared by recursing into them instead of by value
+const STRUCTURAL_KEYWORDS: [&str; 5] = ["properties", "required", "indices", "items", "additionalProperties"];
+
+/// Kind of a structural change
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Changed => "Changed",
+        }
+    }
+}
+
+/// Part of a contract that a change applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeTarget {
+    DocumentType,
+    Property,
+    Constraint,
+    Index,
+}
+
+impl ChangeTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeTarget::DocumentType => "Document type",
+            ChangeTarget::Property => "Property",
+            ChangeTarget::Constraint => "Constraint",
+            ChangeTarget::Index => "Index",
+        }
+    }
+}
+
+/// A single difference between two sets of document schemas
+#[derive(Debug, Clone)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub target: ChangeTarget,
+    /// JSON pointer into the documents object, e.g. `/note/properties/message/maxLength`
+    pub path: String,
+    /// Human-readable explanation of the change
+    pub description: String,
+    /// Whether documents or queries that were valid before the change can break
+    pub breaking: bool,
+}
+
+impl SchemaChange {
+    fn new(kind: ChangeKind, target: ChangeTarget, path: String, description: String, breaking: bool) -> Self {
+        Self { kind, target, path, description, breaking }
+    }
+}
+
+/// Escapes a key for use as a JSON pointer segment
+pub fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Finds the document schemas in a pasted contract, which may be a full data contract or just its documents
+pub fn documents_of(contract: &Value) -> Option<&Map<String, Value>> {
+    let contract_obj = contract.as_object()?;
+    match ["documents", "documentSchemas"].into_iter().find_map(|key| contract_obj.get(key)) {
+        Some(documents) => documents.as_object(),
+        None => Some(contract_obj),
+    }
+}
+
+/// Builds an RFC 6902 JSON Patch that turns `old` into `new`
+pub fn json_patch(old: &Value, new: &Value) -> Vec<Value> {
+    let mut operations = Vec::new();
+    patch_value("", old, new, &mut operations);
+    operations
+}
+
+fn patch_value(path: &str, old: &Value, new: &Value, operations: &mut Vec<Value>) {
+    match (old, new) {
+        (Value::Object(old_obj), Value::Object(new_obj)) => {
+            for (key, old_value) in old_obj {
+                let key_path = format!("{}/{}", path, pointer_segment(key));
+                match new_obj.get(key) {
+                    Some(new_value) => patch_value(&key_path, old_value, new_value, operations),
+                    None => operations.push(json!({"op": "remove", "path": key_path})),
+                }
+            }
+            for (key, new_value) in new_obj.iter().filter(|(key, _)| !old_obj.contains_key(*key)) {
+                operations.push(json!({"op": "add", "path": format!("{}/{}", path, pointer_segment(key)), "value": new_value}));
+            }
+        }
+        (Value::Array(old_arr), Value::Array(new_arr)) => {
+            for (i, (old_value, new_value)) in old_arr.iter().zip(new_arr.iter()).enumerate() {
+                patch_value(&format!("{}/{}", path, i), old_value, new_value, operations);
+            }
+            // Remove from the end so that the remaining indices stay valid
+            for i in (new_arr.len()..old_arr.len()).rev() {
+                operations.push(json!({"op": "remove", "path": format!("{}/{}", path, i)}));
+            }
+            for new_value in new_arr.iter().skip(old_arr.len()) {
+                operations.push(json!({"op": "add", "path": format!("{}/-", path), "value": new_value}));
+            }
+        }
+        _ if old != new => operations.push(json!({"op": "replace", "path": path, "value": new})),
+        _ => {}
+    }
+}
+
+/// Compares two documents objects (document type name to schema) and lists every structural change
+pub fn diff_documents(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    for (name, old_schema) in old {
+        let path = format!("/{}", pointer_segment(name));
+        match new.get(name) {
+            Some(new_schema) => diff_document_type(&path, name, old_schema, new_schema, &mut changes),
+            None => changes.push(SchemaChange::new(ChangeKind::Removed, ChangeTarget::DocumentType, path, format!("Document type \"{}\" was removed", name), true)),
+        }
+    }
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        let path = format!("/{}", pointer_segment(name));
+        changes.push(SchemaChange::new(ChangeKind::Added, ChangeTarget::DocumentType, path, format!("Document type \"{}\" was added", name), false));
+    }
+    changes
+}
+
+fn diff_document_type(path: &str, name: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    diff_keywords(path, name, old, new, changes);
+    diff_object_schema(path, name, old, new, changes);
+    diff_indices(path, name, old, new, changes);
+}
+
+/// Compares the properties, required fields and additionalProperties of two object schemas
+fn diff_object_schema(path: &str, name: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let empty = Map::new();
+    let old_props = old.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+    let new_props = new.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+    for (prop_name, old_prop) in old_props {
+        let prop_path = format!("{}/properties/{}", path, pointer_segment(prop_name));
+        let display_name = format!("{}.{}", name, prop_name);
+        match new_props.get(prop_name) {
+            Some(new_prop) => diff_property(&prop_path, &display_name, old_prop, new_prop, changes),
+            None => changes.push(SchemaChange::new(ChangeKind::Removed, ChangeTarget::Property, prop_path, format!("Property \"{}\" was removed", display_name), true)),
+        }
+    }
+    for prop_name in new_props.keys().filter(|prop_name| !old_props.contains_key(*prop_name)) {
+        let prop_path = format!("{}/properties/{}", path, pointer_segment(prop_name));
+        changes.push(SchemaChange::new(ChangeKind::Added, ChangeTarget::Property, prop_path, format!("Property \"{}.{}\" was added", name, prop_name), false));
+    }
+
+    let old_required = string_set(old.get("required"));
+    let new_required = string_set(new.get("required"));
+    for field in new_required.iter().filter(|field| !old_required.contains(field)) {
+        changes.push(SchemaChange::new(ChangeKind::Added, ChangeTarget::Constraint, format!("{}/required", path), format!("\"{}.{}\" became required", name, field), true));
+    }
+    for field in old_required.iter().filter(|field| !new_required.contains(field)) {
+        changes.push(SchemaChange::new(ChangeKind::Removed, ChangeTarget::Constraint, format!("{}/required", path), format!("\"{}.{}\" is no longer required", name, field), false));
+    }
+
+    let old_additional = old.get("additionalProperties").and_then(|v| v.as_bool()).unwrap_or(true);
+    let new_additional = new.get("additionalProperties").and_then(|v| v.as_bool()).unwrap_or(true);
+    if old_additional != new_additional {
+        changes.push(SchemaChange::new(ChangeKind::Changed, ChangeTarget::Constraint, format!("{}/additionalProperties", path), format!("additionalProperties of \"{}\" changed from {} to {}", name, old_additional, new_additional), !new_additional));
+    }
+}
+
+/// Compares two property schemas keyword by keyword
+fn diff_property(path: &str, name: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let old_type = old.get("type");
+    let new_type = new.get("type");
+    if old_type != new_type {
+        changes.push(SchemaChange::new(ChangeKind::Changed, ChangeTarget::Property, format!("{}/type", path), format!("Type of \"{}\" changed from {} to {}", name, display(old_type), display(new_type)), true));
+        return;
+    }
+
+    diff_keywords(path, name, old, new, changes);
+
+    if new_type.and_then(|t| t.as_str()) == Some("object") {
+        diff_object_schema(path, name, old, new, changes);
+    }
+    if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+        diff_property(&format!("{}/items", path), &format!("{}[]", name), old_items, new_items, changes);
+    }
+}
+
+/// Compares the non-structural keywords of two schemas, such as constraints and annotations
+fn diff_keywords(path: &str, name: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let empty = Map::new();
+    let old_obj = old.as_object().unwrap_or(&empty);
+    let new_obj = new.as_object().unwrap_or(&empty);
+    let keywords = old_obj.keys().chain(new_obj.keys().filter(|k| !old_obj.contains_key(*k)));
+    for keyword in keywords {
+        if keyword == "type" || STRUCTURAL_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        let old_value = old_obj.get(keyword);
+        let new_value = new_obj.get(keyword);
+        if old_value == new_value {
+            continue;
+        }
+        let keyword_path = format!("{}/{}", path, pointer_segment(keyword));
+        let kind = match (old_value, new_value) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        };
+        let breaking = is_tightening(keyword, old_value, new_value);
+        let description = match kind {
+            ChangeKind::Added => format!("{} of \"{}\" was set to {}", keyword, name, display(new_value)),
+            ChangeKind::Removed => format!("{} of \"{}\" was removed (was {})", keyword, name, display(old_value)),
+            ChangeKind::Changed => format!("{} of \"{}\" changed from {} to {}", keyword, name, display(old_value), display(new_value)),
+        };
+        changes.push(SchemaChange::new(kind, ChangeTarget::Constraint, keyword_path, description, breaking));
+    }
+}
+
+/// Whether changing a keyword from one value to another can invalidate existing documents
+fn is_tightening(keyword: &str, old: Option<&Value>, new: Option<&Value>) -> bool {
+    if ANNOTATION_KEYWORDS.contains(&keyword) {
+        return false;
+    }
+    match (old, new) {
+        // Removing a constraint only ever loosens it
+        (Some(_), None) => false,
+        (None, Some(_)) => true,
+        (Some(old), Some(new)) => {
+            let (old_number, new_number) = (old.as_f64(), new.as_f64());
+            match (old_number, new_number) {
+                (Some(old_number), Some(new_number)) if LOWER_BOUNDS.contains(&keyword) => new_number > old_number,
+                (Some(old_number), Some(new_number)) if UPPER_BOUNDS.contains(&keyword) => new_number < old_number,
+                _ => true,
+            }
+        }
+        (None, None) => false,
+    }
+}
+
+/// Compares the indices of two document types, matched by index name
+fn diff_indices(path: &str, name: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let empty = Vec::new();
+    let old_indices = old.get("indices").and_then(|i| i.as_array()).unwrap_or(&empty);
+    let new_indices = new.get("indices").and_then(|i| i.as_array()).unwrap_or(&empty);
+    let index_name = |index: &Value| index.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+    let old_props: Vec<String> = old.get("properties").and_then(|p| p.as_object()).map(|p| p.keys().cloned().collect()).unwrap_or_default();
+
+    for (position, old_index) in old_indices.iter().enumerate() {
+        let old_name = index_name(old_index);
+        let index_path = format!("{}/indices/{}", path, position);
+        match new_indices.iter().find(|new_index| index_name(new_index) == old_name) {
+            Some(new_index) if new_index != old_index => {
+                changes.push(SchemaChange::new(ChangeKind::Changed, ChangeTarget::Index, index_path, format!("Index \"{}\" of \"{}\" was changed", old_name, name), true));
+            }
+            Some(_) => {}
+            None => {
+                changes.push(SchemaChange::new(ChangeKind::Removed, ChangeTarget::Index, index_path, format!("Index \"{}\" of \"{}\" was removed", old_name, name), true));
+            }
+        }
+    }
+    for (position, new_index) in new_indices.iter().enumerate() {
+        let new_name = index_name(new_index);
+        if old_indices.iter().any(|old_index| index_name(old_index) == new_name) {
+            continue;
+        }
+        let index_path = format!("{}/indices/{}", path, position);
+        let unique = new_index.get("unique").and_then(|u| u.as_bool()).unwrap_or(false);
+        let uses_existing = index_property_names(new_index).iter().any(|prop| old_props.contains(prop));
+        let (breaking, reason) = if unique {
+            (true, " (new unique indices can't be added to an existing document type)")
+        } else if uses_existing {
+            (true, " (existing documents would not be indexed by it)")
+        } else {
+            (false, "")
+        };
+        changes.push(SchemaChange::new(ChangeKind::Added, ChangeTarget::Index, index_path, format!("Index \"{}\" of \"{}\" was added{}", new_name, name, reason), breaking));
+    }
+}
+
+/// Names of the properties an index is defined over, in order
+pub fn index_property_names(index: &Value) -> Vec<String> {
+    index.get("properties")
+        .and_then(|p| p.as_array())
+        .map(|props| props.iter().filter_map(|p| p.as_object()).flat_map(|p| p.keys().cloned()).collect())
+        .unwrap_or_default()
+}
+
+fn string_set(value: Option<&Value>) -> Vec<String> {
+    value.and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+fn display(value: Option<&Value>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| String::from("nothing"))
+}
+
+impl Model {
+    pub fn compare(&mut self) {
+        self.json_object = self.generate_json_object();
+        let current = self.documents_json();
+        let pasted: Value = match serde_json::from_str(&self.compare_json) {
+            Ok(pasted) => pasted,
+            Err(e) => {
+                self.compare_changes = vec![];
+                self.compare_patch = String::new();
+                self.compare_messages = vec![format!("Invalid JSON: {}", e)];
+                return;
+            }
+        };
+        match (documents_of(&pasted), current.as_object()) {
+            (Some(old), Some(new)) => {
+                self.compare_changes = diff_documents(old, new);
+                let patch = json_patch(&Value::Object(old.clone()), &current);
+                self.compare_patch = serde_json::to_string_pretty(&patch).unwrap();
+                self.compare_messages = vec![];
+            }
+            _ => {
+                self.compare_changes = vec![];
+                self.compare_patch = String::new();
+                self.compare_messages = vec![String::from("The pasted contract must be a JSON object")];
+            }
+        }
+    }
+
+    pub fn view_diff(&self, ctx: &yew::Context<Self>) -> Html {
+        let breaking = self.compare_changes.iter().filter(|change| change.breaking).count();
+        html! {
+            <p class="output-container">
+                <h2>{"Compare contracts"}</h2>
+                <textarea class="textarea" placeholder="Paste a contract to compare with the current one" value={self.compare_json.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateCompareJson(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::Compare)}>{"Compare"}</button></div>
+                <ul class="error-text">
+                    { for self.compare_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                </ul>
+                {
+                    if !self.compare_patch.is_empty() {
+                        html! {
+                            <>
+                            <p><b>{format!("{} changes, {} breaking", self.compare_changes.len(), breaking)}</b></p>
+                            <table>
+                                <tbody>
+                                    <tr>
+                                        <th>{"Change"}</th>
+                                        <th>{"Path"}</th>
+                                        <th>{"Description"}</th>
+                                        <th>{"Compatibility"}</th>
+                                    </tr>
+                                    {for self.compare_changes.iter().map(|change| html! {
+                                        <tr>
+                                            <td>{format!("{} {}", change.kind.label(), change.target.label().to_lowercase())}</td>
+                                            <td>{change.path.clone()}</td>
+                                            <td>{change.description.clone()}</td>
+                                            <td class={if change.breaking {"error-text"} else {"passed-text"}}>{if change.breaking {"Breaking"} else {"Backward-compatible"}}</td>
+                                        </tr>
+                                    })}
+                                </tbody>
+                            </table>
+                            <h3>{"JSON Patch:"}</h3>
+                            <pre>
+                            <textarea class="textarea" readonly=true value={self.compare_patch.clone()}></textarea>
+                            </pre>
+                            <div><button class="button2" onclick={ctx.link().callback(|_| Msg::Download(Download::Patch))}>{"Download patch"}</button></div>
+                            </>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </p>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn documents(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    /// The changes as (path, description, breaking), for comparing in one assertion
+    fn summary(changes: &[SchemaChange]) -> Vec<(&str, &str, bool)> {
+        changes.iter().map(|change| (change.path.as_str(), change.description.as_str(), change.breaking)).collect()
+    }
+
+    #[test]
+    fn diff_documents_flags_tightened_constraints_as_breaking() {
+        let old = documents(json!({"note": {"type": "object", "properties": {"message": {"type": "string", "maxLength": 100, "minLength": 1}}}}));
+        let new = documents(json!({"note": {"type": "object", "properties": {"message": {"type": "string", "maxLength": 50, "minLength": 0, "description": "Text"}}}}));
+        assert_eq!(summary(&diff_documents(&old, &new)), [
+            ("/note/properties/message/maxLength", "maxLength of \"note.message\" changed from 100 to 50", true),
+            ("/note/properties/message/minLength", "minLength of \"note.message\" changed from 1 to 0", false),
+            ("/note/properties/message/description", "description of \"note.message\" was set to \"Text\"", false),
+        ]);
+    }
+
+    #[test]
+    fn diff_documents_lists_added_and_removed_parts() {
+        let old = documents(json!({
+            "note": {"type": "object", "properties": {"message": {"type": "string"}, "a/b": {"type": "integer"}}, "required": ["message"], "indices": [{"name": "byMessage", "properties": [{"message": "asc"}]}]},
+            "old": {"type": "object", "properties": {}},
+        }));
+        let new = documents(json!({
+            "note": {"type": "object", "properties": {"message": {"type": "integer"}, "author": {"type": "string"}}, "required": ["author"], "indices": [{"name": "byAuthor", "properties": [{"author": "asc"}], "unique": true}], "additionalProperties": false},
+            "new": {"type": "object", "properties": {}},
+        }));
+        assert_eq!(summary(&diff_documents(&old, &new)), [
+            ("/note/properties/message/type", "Type of \"note.message\" changed from \"string\" to \"integer\"", true),
+            ("/note/properties/a~1b", "Property \"note.a/b\" was removed", true),
+            ("/note/properties/author", "Property \"note.author\" was added", false),
+            ("/note/required", "\"note.author\" became required", true),
+            ("/note/required", "\"note.message\" is no longer required", false),
+            ("/note/additionalProperties", "additionalProperties of \"note\" changed from true to false", true),
+            ("/note/indices/0", "Index \"byMessage\" of \"note\" was removed", true),
+            ("/note/indices/0", "Index \"byAuthor\" of \"note\" was added (new unique indices can't be added to an existing document type)", true),
+            ("/old", "Document type \"old\" was removed", true),
+            ("/new", "Document type \"new\" was added", false),
+        ]);
+    }
+
+    #[test]
+    fn diff_documents_finds_nothing_for_identical_schemas() {
+        let schemas = documents(json!({"note": {"type": "object", "properties": {"tags": {"type": "array", "items": {"type": "string"}}}}}));
+        assert!(diff_documents(&schemas, &schemas).is_empty());
+    }
+
+    #[test]
+    fn json_patch_turns_old_into_new() {
+        let old = json!({"a": 1, "b": {"c": [1, 2, 3]}, "d/e": true});
+        let new = json!({"a": 2, "b": {"c": [1, 5]}, "f": null});
+        assert_eq!(json_patch(&old, &new), [
+            json!({"op": "replace", "path": "/a", "value": 2}),
+            json!({"op": "replace", "path": "/b/c/1", "value": 5}),
+            json!({"op": "remove", "path": "/b/c/2"}),
+            json!({"op": "remove", "path": "/d~1e"}),
+            json!({"op": "add", "path": "/f", "value": null}),
+        ]);
+        assert_eq!(json_patch(&json!([1]), &json!([1, 2, 3])), [
+            json!({"op": "add", "path": "/-", "value": 2}),
+            json!({"op": "add", "path": "/-", "value": 3}),
+        ]);
+        assert!(json_patch(&new, &new).is_empty());
+    }
+}
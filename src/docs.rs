@@ -0,0 +1,229 @@
+//! Human-readable documentation of the contract as Markdown or standalone HTML
+
+use serde_json::Value;
+
+use crate::{diff::index_property_names, export::required_names};
+
+/// Document type keywords that are described in their own sections rather than listed as options
+const DESCRIBED_KEYWORDS: [&str; 5] = ["type", "properties", "indices", "required", "$comment"];
+
+/// Property keywords that are shown in their own columns or expanded as nested rows rather than listed as constraints
+const COLUMN_KEYWORDS: [&str; 6] = ["type", "description", "$comment", "properties", "required", "items"];
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; max-width: 960px; margin: 2em auto; color: #222; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; }
+code { background: #f3f3f3; padding: 0 2px; }";
+
+/// One row of a property table. Nested properties are flattened with dotted names.
+struct PropertyRow {
+    name: String,
+    data_type: String,
+    constraints: Vec<String>,
+    required: bool,
+    description: String,
+    comment: String,
+}
+
+/// Flattens the properties of an object schema into table rows, nested properties after their parent
+fn property_rows(schema: &Value, prefix: &str, rows: &mut Vec<PropertyRow>) {
+    let required = required_names(schema);
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (name, property) in properties {
+            let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+            let text = |keyword: &str| property.get(keyword).and_then(|t| t.as_str()).unwrap_or_default().to_string();
+            let mut data_type = text("type");
+            if let Some(item_type) = property.get("items").and_then(|i| i.get("type")).and_then(|t| t.as_str()) {
+                data_type = format!("{} of {}", data_type, item_type);
+            }
+            let constraints = property
+                .as_object()
+                .map(|obj| {
+                    obj.iter()
+                        .filter(|(keyword, _)| !COLUMN_KEYWORDS.contains(&keyword.as_str()))
+                        .map(|(keyword, value)| format!("{}: {}", keyword, value))
+                        .collect()
+                })
+                .unwrap_or_default();
+            rows.push(PropertyRow {
+                name: path.clone(),
+                data_type,
+                constraints,
+                required: required.contains(name),
+                description: text("description"),
+                comment: text("$comment"),
+            });
+            property_rows(property, &path, rows);
+        }
+    }
+}
+
+/// Describes each index as its name, uniqueness and properties with their sort order
+fn index_descriptions(schema: &Value) -> Vec<(String, bool, String)> {
+    schema.get("indices")
+        .and_then(|i| i.as_array())
+        .map(|indices| {
+            indices.iter()
+                .map(|index| {
+                    let name = index.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+                    let unique = index.get("unique").and_then(|u| u.as_bool()).unwrap_or(false);
+                    let properties = index_property_names(index)
+                        .iter()
+                        .map(|prop| {
+                            let order = index["properties"].as_array()
+                                .and_then(|props| props.iter().find_map(|p| p.get(prop)))
+                                .and_then(|o| o.as_str())
+                                .unwrap_or("asc");
+                            format!("{} {}", prop, order)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    (name, unique, properties)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Document-level keywords other than the ones with their own sections, e.g. `additionalProperties`
+fn document_options(schema: &Value) -> Vec<(String, String)> {
+    schema.as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter(|(keyword, _)| !DESCRIBED_KEYWORDS.contains(&keyword.as_str()))
+                .map(|(keyword, value)| (keyword.clone(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn comment(schema: &Value) -> Option<&str> {
+    schema.get("$comment").and_then(|c| c.as_str()).filter(|c| !c.is_empty())
+}
+
+/// Escapes text for a Markdown table cell
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Generates Markdown with a section per document type
+pub fn generate_markdown(documents: &Value) -> String {
+    let mut out = String::from("# Data Contract\n");
+    if let Some(documents) = documents.as_object() {
+        out.push_str("\nDocument types:\n\n");
+        for name in documents.keys() {
+            out.push_str(&format!("- [{}](#{})\n", name, name.to_lowercase()));
+        }
+        for (name, schema) in documents {
+            out.push_str(&format!("\n## {}\n\n", name));
+            if let Some(text) = comment(schema) {
+                out.push_str(&format!("{}\n\n", text));
+            }
+            out.push_str("### Properties\n\n");
+            let mut rows = Vec::new();
+            property_rows(schema, "", &mut rows);
+            if rows.is_empty() {
+                out.push_str("No properties.\n");
+            } else {
+                out.push_str("| Name | Type | Constraints | Required | Description | Comment |\n");
+                out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+                for row in rows {
+                    let constraints = row.constraints.iter().map(|c| format!("`{}`", c)).collect::<Vec<_>>().join("<br>");
+                    out.push_str(&format!(
+                        "| `{}` | {} | {} | {} | {} | {} |\n",
+                        row.name,
+                        row.data_type,
+                        md_cell(&constraints),
+                        if row.required { "Yes" } else { "No" },
+                        md_cell(&row.description),
+                        md_cell(&row.comment)
+                    ));
+                }
+            }
+            let indices = index_descriptions(schema);
+            if !indices.is_empty() {
+                out.push_str("\n### Indices\n\n| Name | Unique | Properties |\n| --- | --- | --- |\n");
+                for (index_name, unique, properties) in indices {
+                    out.push_str(&format!("| `{}` | {} | {} |\n", index_name, if unique { "Yes" } else { "No" }, md_cell(&properties)));
+                }
+            }
+            let options = document_options(schema);
+            if !options.is_empty() {
+                out.push_str("\n### Options\n\n");
+                for (keyword, value) in options {
+                    out.push_str(&format!("- `{}`: `{}`\n", keyword, value));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Generates a standalone HTML page with the same content as the Markdown export
+pub fn generate_html(documents: &Value) -> String {
+    let mut body = String::from("<h1>Data Contract</h1>\n");
+    if let Some(documents) = documents.as_object() {
+        body.push_str("<p>Document types:</p>\n<ul>\n");
+        for name in documents.keys() {
+            body.push_str(&format!("<li><a href=\"#{0}\">{0}</a></li>\n", html_escape(name)));
+        }
+        body.push_str("</ul>\n");
+        for (name, schema) in documents {
+            body.push_str(&format!("<h2 id=\"{0}\">{0}</h2>\n", html_escape(name)));
+            if let Some(text) = comment(schema) {
+                body.push_str(&format!("<p>{}</p>\n", html_escape(text)));
+            }
+            body.push_str("<h3>Properties</h3>\n");
+            let mut rows = Vec::new();
+            property_rows(schema, "", &mut rows);
+            if rows.is_empty() {
+                body.push_str("<p>No properties.</p>\n");
+            } else {
+                body.push_str("<table>\n<tr><th>Name</th><th>Type</th><th>Constraints</th><th>Required</th><th>Description</th><th>Comment</th></tr>\n");
+                for row in rows {
+                    let constraints = row.constraints.iter().map(|c| format!("<code>{}</code>", html_escape(c))).collect::<Vec<_>>().join("<br>");
+                    body.push_str(&format!(
+                        "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                        html_escape(&row.name),
+                        html_escape(&row.data_type),
+                        constraints,
+                        if row.required { "Yes" } else { "No" },
+                        html_escape(&row.description),
+                        html_escape(&row.comment)
+                    ));
+                }
+                body.push_str("</table>\n");
+            }
+            let indices = index_descriptions(schema);
+            if !indices.is_empty() {
+                body.push_str("<h3>Indices</h3>\n<table>\n<tr><th>Name</th><th>Unique</th><th>Properties</th></tr>\n");
+                for (index_name, unique, properties) in indices {
+                    body.push_str(&format!(
+                        "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                        html_escape(&index_name),
+                        if unique { "Yes" } else { "No" },
+                        html_escape(&properties)
+                    ));
+                }
+                body.push_str("</table>\n");
+            }
+            let options = document_options(schema);
+            if !options.is_empty() {
+                body.push_str("<h3>Options</h3>\n<ul>\n");
+                for (keyword, value) in options {
+                    body.push_str(&format!("<li><code>{}</code>: <code>{}</code></li>\n", html_escape(&keyword), html_escape(&value)));
+                }
+                body.push_str("</ul>\n");
+            }
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Data Contract</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        HTML_STYLE, body
+    )
+}
@@ -0,0 +1,165 @@
+//! Validation of sample documents against a document type
+
+use std::sync::Arc;
+
+use dpp::{
+    document::document_validator::DocumentValidator,
+    platform_value::{self, ReplacementType},
+    prelude::{DataContract, Identifier},
+    version::ProtocolVersionValidator,
+};
+use serde_json::{json, Value};
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, InputEvent, TargetCast};
+
+use crate::{data_contract_factory, Model, Msg, is_identifier};
+
+/// Validation result of one sample document
+pub struct DocumentResult {
+    /// Which document this is, e.g. "Document 2" or "Line 5"
+    pub label: String,
+    /// Validation errors with their paths
+    pub errors: Vec<String>,
+}
+
+/// Splits the input into documents. Accepts a single JSON object, a JSON array or JSONL.
+/// Documents that can't be parsed are returned as errors labeled with their line.
+pub fn parse_documents(input: &str) -> Vec<(String, Result<Value, String>)> {
+    let input = input.trim();
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(documents)) => documents
+            .into_iter()
+            .enumerate()
+            .map(|(i, document)| (format!("Document {}", i + 1), Ok(document)))
+            .collect(),
+        Ok(document) => vec![(String::from("Document 1"), Ok(document))],
+        Err(_) => input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (format!("Line {}", i + 1), serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))))
+            .collect(),
+    }
+}
+
+/// Collects the dotted paths of byte array properties in a document schema, split into identifiers and other binary data
+fn binary_paths(schema: &Value, prefix: &str, identifiers: &mut Vec<String>, binaries: &mut Vec<String>) {
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (name, property) in properties {
+            let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+            if is_identifier(property) {
+                identifiers.push(path);
+            } else if property.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+                binaries.push(path);
+            } else if property.get("type").and_then(|t| t.as_str()) == Some("object") {
+                binary_paths(property, &path, identifiers, binaries);
+            }
+        }
+    }
+}
+
+/// Fills in the system fields DPP expects on a raw document and converts byte arrays from their JSON form
+fn raw_document(document: &Value, document_type: &str, schema: &Value, data_contract: &DataContract) -> Result<platform_value::Value, String> {
+    let mut document = document.clone();
+    let document_obj = document.as_object_mut().ok_or_else(|| String::from("A document must be a JSON object"))?;
+    let defaults = [
+        ("$protocolVersion", json!(1)),
+        ("$id", json!(Identifier::random().to_string(platform_value::string_encoding::Encoding::Base58))),
+        ("$type", json!(document_type)),
+        ("$dataContractId", json!(data_contract.id.to_string(platform_value::string_encoding::Encoding::Base58))),
+        ("$ownerId", json!(data_contract.owner_id.to_string(platform_value::string_encoding::Encoding::Base58))),
+        ("$revision", json!(1)),
+    ];
+    for (field, value) in defaults {
+        document_obj.entry(field).or_insert(value);
+    }
+
+    let mut identifiers = vec![String::from("$id"), String::from("$dataContractId"), String::from("$ownerId")];
+    let mut binaries = vec![];
+    binary_paths(schema, "", &mut identifiers, &mut binaries);
+    let mut raw: platform_value::Value = document.into();
+    raw.replace_at_paths(identifiers.iter().map(|p| p.as_str()), ReplacementType::Identifier)
+        .map_err(|e| format!("Invalid identifier: {}", e))?;
+    raw.replace_at_paths(binaries.iter().map(|p| p.as_str()), ReplacementType::BinaryBytes)
+        .map_err(|e| format!("Invalid byte array: {}", e))?;
+    Ok(raw)
+}
+
+impl Model {
+    /// Name of the document type sample documents are validated against
+    pub fn selected_sample_document_type(&self) -> String {
+        if self.sample_document_type.is_empty() {
+            self.document_types.first().map(|doc_type| doc_type.name.clone()).unwrap_or_default()
+        } else {
+            self.sample_document_type.clone()
+        }
+    }
+
+    pub fn validate_sample_documents(&mut self) {
+        self.json_object = self.generate_json_object();
+        let documents = self.documents_json();
+        let document_type = self.selected_sample_document_type();
+        let schema = match documents.get(&document_type) {
+            Some(schema) => schema.clone(),
+            None => {
+                self.sample_results = vec![DocumentResult { label: String::from("Contract"), errors: vec![format!("Unknown document type \"{}\"", document_type)] }];
+                return;
+            }
+        };
+        let created = match data_contract_factory().create(Identifier::random(), documents.into(), None, None) {
+            Ok(created) => created,
+            Err(e) => {
+                self.sample_results = vec![DocumentResult { label: String::from("Contract"), errors: vec![format!("The contract is invalid: {}", e)] }];
+                return;
+            }
+        };
+        let validator = DocumentValidator::new(Arc::new(ProtocolVersionValidator::default()));
+        self.sample_results = parse_documents(&self.sample_documents_input)
+            .into_iter()
+            .map(|(label, document)| {
+                let errors = match document.and_then(|document| raw_document(&document, &document_type, &schema, &created.data_contract)) {
+                    Ok(raw) => match validator.validate(&raw, &created.data_contract) {
+                        Ok(result) => self.extract_basic_error_messages(&result.errors),
+                        Err(e) => vec![format!("Validation failed: {}", e)],
+                    },
+                    Err(message) => vec![message],
+                };
+                DocumentResult { label, errors }
+            })
+            .collect();
+    }
+
+    pub fn view_sample_documents(&self, ctx: &yew::Context<Self>) -> Html {
+        let selected = self.selected_sample_document_type();
+        html! {
+            <p class="output-container">
+                <h2>{"Validate documents"}</h2>
+                <h3>{"Document type"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| Msg::UpdateSampleDocumentType(e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                    {for self.document_types.iter().map(|doc_type| html! {
+                        <option value={doc_type.name.clone()} selected={doc_type.name == selected}>{doc_type.name.clone()}</option>
+                    })}
+                </select>
+                <h3>{"Documents"}</h3>
+                <textarea class="textarea" placeholder="Paste a JSON document, an array of documents or JSONL" value={self.sample_documents_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateSampleDocuments(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::ValidateSampleDocuments)}>{"Validate documents"}</button></div>
+                {for self.sample_results.iter().map(|result| html! {
+                    <>
+                    <h3>{result.label.clone()}</h3>
+                    {
+                        if result.errors.is_empty() {
+                            html! {<p class="passed-text">{"Validation passed ✓"}</p>}
+                        } else {
+                            html! {
+                                <ul class="error-text">
+                                    { for result.errors.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                                </ul>
+                            }
+                        }
+                    }
+                    </>
+                })}
+            </p>
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Duplicating document types, properties and indices
+
+use crate::Model;
+
+/// Returns `name` with a `_copy` suffix, numbered if that name is taken too
+pub fn unique_name(name: &str, existing: &[String]) -> String {
+    let base = format!("{}_copy", name);
+    if !existing.contains(&base) {
+        return base;
+    }
+    (2..).map(|n| format!("{}{}", base, n)).find(|candidate| !existing.contains(candidate)).unwrap_or(base)
+}
+
+impl Model {
+    /// Inserts a copy of a document type after it. Indices refer to properties by name and the copied
+    /// properties keep their names, so the copy's indices point at the copy's own properties.
+    pub fn duplicate_document_type(&mut self, index: usize) {
+        let mut copy = self.document_types[index].clone();
+        let names = self.document_types.iter().map(|doc_type| doc_type.name.clone()).collect::<Vec<_>>();
+        copy.name = unique_name(&copy.name, &names);
+        self.copy_document_type_references(&self.document_types[index].name.clone(), &copy.name);
+        self.document_types.insert(index + 1, copy);
+        self.remap_document_types(|i| Some(if i > index { i + 1 } else { i }));
+    }
+
+    /// Inserts a copy of a top-level property after it
+    pub fn duplicate_property(&mut self, doc_index: usize, prop_index: usize) {
+        let document_type = &mut self.document_types[doc_index];
+        let names = document_type.properties.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+        let mut copy = document_type.properties[prop_index].clone();
+        let original = copy.name.clone();
+        copy.name = unique_name(&original, &names);
+        let (document_type_name, copy_name) = (document_type.name.clone(), copy.name.clone());
+        document_type.properties.insert(prop_index + 1, copy);
+        self.copy_property_references(&document_type_name, &original, &copy_name);
+    }
+
+    /// Inserts a copy of a nested property after it
+    pub fn duplicate_rec_property(&mut self, doc_index: usize, prop_index: usize, rec_prop_index: usize) {
+        let document_type = &mut self.document_types[doc_index];
+        let (document_type_name, parent) = (document_type.name.clone(), document_type.properties[prop_index].name.clone());
+        if let Some(properties) = document_type.properties[prop_index].properties.as_mut() {
+            let names = properties.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+            let mut copy = properties[rec_prop_index].clone();
+            let original = copy.name.clone();
+            copy.name = unique_name(&original, &names);
+            let copy_name = copy.name.clone();
+            properties.insert(rec_prop_index + 1, copy);
+            self.copy_property_references(&document_type_name, &format!("{}.{}", parent, original), &format!("{}.{}", parent, copy_name));
+        }
+    }
+
+    /// Inserts a copy of an index after it
+    pub fn duplicate_index(&mut self, doc_index: usize, index_index: usize) {
+        let indices = &mut self.document_types[doc_index].indices;
+        let names = indices.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+        let mut copy = indices[index_index].clone();
+        copy.name = unique_name(&copy.name, &names);
+        indices.insert(index_index + 1, copy);
+    }
+}
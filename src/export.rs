@@ -0,0 +1,111 @@
+//! Exports of the contract to other formats
+
+use serde_json::Value;
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, TargetCast};
+
+use crate::{docs, files::Download, js_sdk, json_schema, rust_structs, typescript, yaml, Model, Msg};
+
+/// Formats the contract can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExportFormat {
+    #[default]
+    TypeScript,
+    RustStructs,
+    JsSdk,
+    Markdown,
+    Html,
+    JsonSchema,
+    Yaml,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 7] = [
+        ExportFormat::TypeScript,
+        ExportFormat::RustStructs,
+        ExportFormat::JsSdk,
+        ExportFormat::Markdown,
+        ExportFormat::Html,
+        ExportFormat::JsonSchema,
+        ExportFormat::Yaml,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::TypeScript => "TypeScript definitions",
+            ExportFormat::RustStructs => "Rust structs",
+            ExportFormat::JsSdk => "Dash JS SDK script",
+            ExportFormat::Markdown => "Markdown documentation",
+            ExportFormat::Html => "HTML documentation",
+            ExportFormat::JsonSchema => "JSON Schema (2020-12)",
+            ExportFormat::Yaml => "YAML",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL.into_iter().find(|format| format.label() == label).unwrap_or_default()
+    }
+
+    /// Generates the export from the document schemas
+    pub fn generate(&self, documents: &Value) -> String {
+        match self {
+            ExportFormat::TypeScript => typescript::generate(documents),
+            ExportFormat::RustStructs => rust_structs::generate(documents),
+            ExportFormat::JsSdk => js_sdk::generate(documents),
+            ExportFormat::Markdown => docs::generate_markdown(documents),
+            ExportFormat::Html => docs::generate_html(documents),
+            ExportFormat::JsonSchema => json_schema::export(documents),
+            ExportFormat::Yaml => yaml::export(documents),
+        }
+    }
+}
+
+/// Converts a name such as `contact_request` or `contactRequest` to `ContactRequest`
+pub fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Names of the required properties of an object schema
+pub fn required_names(schema: &Value) -> Vec<String> {
+    schema.get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+impl Model {
+    pub fn export(&mut self) {
+        self.json_object = self.generate_json_object();
+        self.export_output = self.export_format.generate(&self.documents_json());
+    }
+
+    pub fn view_export(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <p class="output-container">
+                <h2>{"Export"}</h2>
+                <h3>{"Format"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| Msg::UpdateExportFormat(ExportFormat::from_label(e.target_dyn_into::<HtmlSelectElement>().unwrap().value().as_str())))}>
+                    {for ExportFormat::ALL.iter().map(|format| html! {
+                        <option value={format.label()} selected={*format == self.export_format}>{format.label()}</option>
+                    })}
+                </select>
+                <div>
+                    <button class="button" onclick={ctx.link().callback(|_| Msg::Export)}>{"Export"}</button>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::Download(Download::Export))}>{"Download"}</button>
+                </div>
+                <pre>
+                <textarea class="textarea" readonly=true value={self.export_output.clone()}></textarea>
+                </pre>
+            </p>
+        }
+    }
+}
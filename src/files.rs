@@ -0,0 +1,228 @@
+//! Contract files: upload, drag-and-drop and downloads
+
+use dpp::{data_contract::DataContract, prelude::Identifier};
+use gloo_file::{callbacks::read_as_bytes, Blob, ObjectUrl};
+use serde_json::{Map, Value};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlAnchorElement, HtmlInputElement};
+use yew::{html::Scope, html, DragEvent, Event, Html, TargetCast};
+
+use crate::{data_contract_factory, diff::documents_of, export::ExportFormat, yaml, Model, Msg};
+
+/// What a download button saves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Download {
+    ContractJson,
+    ContractCbor,
+    Export,
+    Transition,
+    Diagram,
+    /// JSON Patch from the compared contract to the current one
+    Patch,
+    /// Generated example documents
+    Samples,
+}
+
+/// Turns a name into a file name stem, e.g. "My Contract" into `my-contract`
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+impl ExportFormat {
+    /// File name suffix of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::TypeScript => "d.ts",
+            ExportFormat::RustStructs => "rs",
+            ExportFormat::JsSdk => "js",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::JsonSchema => "schema.json",
+            ExportFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::TypeScript => "application/typescript",
+            ExportFormat::RustStructs => "text/x-rust",
+            ExportFormat::JsSdk => "text/javascript",
+            ExportFormat::Markdown => "text/markdown",
+            ExportFormat::Html => "text/html",
+            ExportFormat::JsonSchema => "application/schema+json",
+            ExportFormat::Yaml => "application/yaml",
+        }
+    }
+}
+
+/// Reads the document types from an uploaded file, chosen by its extension
+fn documents_from_file(name: &str, bytes: &[u8]) -> Result<(Map<String, Value>, Vec<String>), String> {
+    let extension = name.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => {
+            let contract: Value = serde_json::from_slice(bytes).map_err(|e| format!("{}: invalid JSON: {}", name, e))?;
+            let documents = documents_of(&contract).or_else(|| contract.as_object()).cloned();
+            documents.map(|d| (d, vec![])).ok_or_else(|| format!("{}: the contract must be a JSON object", name))
+        }
+        "yaml" | "yml" => {
+            let text = std::str::from_utf8(bytes).map_err(|_| format!("{}: the file is not UTF-8 text", name))?;
+            yaml::import(text).map_err(|e| format!("{}: {}", name, e))
+        }
+        "cbor" => {
+            let data_contract = DataContract::from_cbor_buffer(bytes).map_err(|e| format!("{}: invalid CBOR data contract: {}", name, e))?;
+            let contract = data_contract.to_json().map_err(|e| format!("{}: could not convert the contract to JSON: {}", name, e))?;
+            documents_of(&contract).cloned().map(|d| (d, vec![])).ok_or_else(|| format!("{}: the contract has no documents", name))
+        }
+        _ => Err(format!("{}: only .json, .cbor and .yaml files can be imported", name)),
+    }
+}
+
+/// Starts a browser download of the given content
+fn save_file(file_name: &str, content: &[u8], mime_type: &str) -> Result<ObjectUrl, String> {
+    let url = ObjectUrl::from(Blob::new_with_options(content, Some(mime_type)));
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<HtmlAnchorElement>().ok())
+        .ok_or_else(|| String::from("Downloads are not available in this browser"))?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    Ok(url)
+}
+
+impl Model {
+    /// File name stem: the document type name for a single document type, otherwise the project name
+    fn file_stem(&self) -> String {
+        let name = match self.document_types.as_slice() {
+            [document_type] => document_type.name.clone(),
+            _ => self.projects.iter().find(|p| p.id == self.current_project).map(|p| p.name.clone()).unwrap_or_default(),
+        };
+        let stem = slug(&name);
+        if stem.is_empty() { String::from("contract") } else { stem }
+    }
+
+    /// Reads the file in the background. It arrives as `Msg::FileLoaded`. Several files are
+    /// rejected since each one replaces the document types.
+    pub fn upload_files(&mut self, files: Vec<web_sys::File>, link: &Scope<Self>) {
+        if files.len() > 1 {
+            self.file_messages = vec![format!("{} files were dropped. Drop one file at a time, since each file replaces the document types", files.len())];
+            return;
+        }
+        self.file_messages = vec![];
+        for file in files {
+            let file = gloo_file::File::from(file);
+            let name = file.name();
+            let link = link.clone();
+            let callback_name = name.clone();
+            let reader = read_as_bytes(&file, move |result| {
+                link.send_message(Msg::FileLoaded(callback_name, result.map_err(|e| e.to_string())));
+            });
+            self.file_readers.insert(name, reader);
+        }
+    }
+
+    pub fn file_loaded(&mut self, name: String, bytes: Result<Vec<u8>, String>) {
+        self.file_readers.remove(&name);
+        match bytes.and_then(|bytes| documents_from_file(&name, &bytes)) {
+            Ok((documents, messages)) => {
+                self.imported_json = Value::Object(documents).to_string();
+                self.file_messages = match self.parse_imported_json() {
+                    Ok(dropped) => messages.into_iter().chain(dropped).collect(),
+                    Err(errors) => errors,
+                };
+            }
+            Err(message) => {
+                self.file_messages = vec![message];
+            }
+        }
+    }
+
+    pub fn download(&mut self, download: Download) {
+        let stem = self.file_stem();
+        let file = match download {
+            Download::ContractJson => {
+                self.json_object = self.generate_json_object();
+                let json = serde_json::to_string_pretty(&self.documents_json()).unwrap_or_default();
+                Ok((format!("{}.json", stem), json.into_bytes(), "application/json"))
+            }
+            Download::ContractCbor => {
+                self.json_object = self.generate_json_object();
+                data_contract_factory()
+                    .create(Identifier::random(), self.documents_json().into(), None, None)
+                    .and_then(|created| created.data_contract.to_cbor_buffer())
+                    .map(|bytes| (format!("{}.cbor", stem), bytes, "application/cbor"))
+                    .map_err(|e| format!("Could not encode the contract as CBOR: {}", e))
+            }
+            Download::Export => {
+                self.export();
+                Ok((format!("{}.{}", stem, self.export_format.extension()), self.export_output.clone().into_bytes(), self.export_format.mime_type()))
+            }
+            Download::Transition => {
+                if self.transition_bytes.is_empty() {
+                    Err(String::from("Build a state transition first"))
+                } else {
+                    Ok((format!("{}-transition.bin", stem), self.transition_bytes.clone(), "application/octet-stream"))
+                }
+            }
+            Download::Diagram => Ok((format!("{}-diagram.svg", stem), self.diagram_svg().into_bytes(), "image/svg+xml")),
+            Download::Patch => {
+                if self.compare_patch.is_empty() {
+                    Err(String::from("Compare with a contract first"))
+                } else {
+                    Ok((format!("{}-patch.json", stem), self.compare_patch.clone().into_bytes(), "application/json-patch+json"))
+                }
+            }
+            Download::Samples => {
+                if self.generated_samples.is_empty() {
+                    Err(String::from("Generate documents first"))
+                } else {
+                    Ok((format!("{}-samples.json", stem), self.generated_samples_json().into_bytes(), "application/json"))
+                }
+            }
+        };
+        match file.and_then(|(file_name, content, mime_type)| save_file(&file_name, &content, mime_type)) {
+            Ok(url) => {
+                // The URL stays valid until the next download so the browser can finish reading it
+                self.download_url = Some(url);
+                self.file_messages = vec![];
+            }
+            Err(message) => {
+                self.file_messages = vec![message];
+            }
+        }
+    }
+
+    /// Upload button for contract files. The contract panel also accepts dropped files.
+    pub fn view_file_upload(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <>
+            <label class="button2">
+                {"Upload file"}
+                <input type="file" class="file-input" accept=".json,.cbor,.yaml,.yml" multiple=false onchange={ctx.link().callback(|e: Event| {
+                    let input = e.target_dyn_into::<HtmlInputElement>().unwrap();
+                    let files = input.files().map(|list| (0..list.length()).filter_map(|i| list.get(i)).collect()).unwrap_or_default();
+                    input.set_value("");
+                    Msg::UploadFiles(files)
+                })} />
+            </label>
+            <ul class="error-text">
+                { for self.file_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+            </ul>
+            </>
+        }
+    }
+}
+
+/// Files dropped on an element
+pub fn dropped_files(e: &DragEvent) -> Vec<web_sys::File> {
+    e.prevent_default();
+    e.data_transfer()
+        .and_then(|transfer| transfer.files())
+        .map(|list| (0..list.length()).filter_map(|i| list.get(i)).collect())
+        .unwrap_or_default()
+}
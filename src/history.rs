@@ -0,0 +1,265 @@
+//! Undo and redo over the document types and their references
+
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlElement};
+use yew::{html, Html};
+
+use crate::{diagram::Reference, DocumentType, Model, Msg};
+
+/// Oldest states are dropped beyond this many
+const MAX_STATES: usize = 100;
+
+/// The document types and references after a change, and the change that produced them
+pub struct HistoryState {
+    pub label: String,
+    /// Consecutive edits of the same field share a group and are merged into one step
+    group: Option<String>,
+    document_types: Vec<DocumentType>,
+    references: Vec<Reference>,
+}
+
+/// Describes the change a message makes, for the history list and for grouping
+pub struct Step {
+    label: String,
+    group: Option<String>,
+}
+
+impl Step {
+    fn new(label: &str) -> Self {
+        Self { label: label.to_string(), group: None }
+    }
+
+    /// An edit of one field. Consecutive edits of the same field are one step, so typing in it is one step.
+    fn typing(label: &str, field: &str, indices: &[usize]) -> Self {
+        Self { label: label.to_string(), group: Some(format!("{}{:?}", field, indices)) }
+    }
+
+    pub fn of(msg: &Msg) -> Self {
+        match msg {
+            // General
+            Msg::Submit => Step::new("Submit"),
+            Msg::AddDocumentType => Step::new("Add document type"),
+            Msg::RemoveDocumentType(_) => Step::new("Remove document type"),
+            Msg::AddProperty(_) => Step::new("Add property"),
+            Msg::RemoveProperty(_, _) => Step::new("Remove property"),
+            Msg::AddIndex(_) => Step::new("Add index"),
+            Msg::RemoveIndex(_, _) => Step::new("Remove index"),
+            Msg::AddIndexProperty(_, _) => Step::new("Add index property"),
+            Msg::DuplicateDocumentType(_) => Step::new("Duplicate document type"),
+            Msg::DuplicateProperty(_, _) => Step::new("Duplicate property"),
+            Msg::DuplicateIndex(_, _) => Step::new("Duplicate index"),
+            Msg::StartDrag(_, _) | Msg::DropItem(_, _) | Msg::MoveItem(_, _, _) => Step::new("Reorder"),
+            Msg::UpdateName(d, _) => Step::typing("Rename document type", "name", &[*d]),
+            Msg::UpdateComment(d, _) => Step::typing("Edit document type comment", "comment", &[*d]),
+            Msg::UpdatePropertyName(d, p, _) => Step::typing("Rename property", "property name", &[*d, *p]),
+            Msg::UpdateIndexName(d, i, _) => Step::typing("Rename index", "index name", &[*d, *i]),
+            Msg::UpdatePropertyType(_, _, _) => Step::new("Change property type"),
+            Msg::UpdateIndexUnique(_, _, _) => Step::new("Change index uniqueness"),
+            Msg::UpdateIndexSorting(_, _, _, _) => Step::new("Change index sort order"),
+            Msg::UpdatePropertyRequired(_, _, _) => Step::new("Change required property"),
+            Msg::UpdatePropertyDescription(d, p, _) => Step::typing("Edit property description", "description", &[*d, *p]),
+            Msg::UpdatePropertyComment(d, p, _) => Step::typing("Edit property comment", "property comment", &[*d, *p]),
+            Msg::UpdateIndexProperty(d, i, p, _) => Step::typing("Edit index property", "index property", &[*d, *i, *p]),
+
+            // Optional property parameters
+            Msg::UpdateStringPropertyMinLength(d, p, _) => Step::typing("Edit min length", "minLength", &[*d, *p]),
+            Msg::UpdateStringPropertyMaxLength(d, p, _) => Step::typing("Edit max length", "maxLength", &[*d, *p]),
+            Msg::UpdateStringPropertyPattern(d, p, _) => Step::typing("Edit pattern", "pattern", &[*d, *p]),
+            Msg::UpdateStringPropertyFormat(d, p, _) => Step::typing("Edit format", "format", &[*d, *p]),
+            Msg::UpdateIntegerPropertyMinimum(d, p, _) => Step::typing("Edit minimum", "minimum", &[*d, *p]),
+            Msg::UpdateIntegerPropertyMaximum(d, p, _) => Step::typing("Edit maximum", "maximum", &[*d, *p]),
+            Msg::UpdateArrayPropertyByteArray(_, _, _) => Step::new("Change byte array"),
+            Msg::UpdateArrayPropertyMinItems(d, p, _) => Step::typing("Edit min items", "minItems", &[*d, *p]),
+            Msg::UpdateArrayPropertyMaxItems(d, p, _) => Step::typing("Edit max items", "maxItems", &[*d, *p]),
+            Msg::UpdateArrayPropertyContentMediaType(d, p, _) => Step::typing("Edit content media type", "contentMediaType", &[*d, *p]),
+            Msg::UpdateObjectPropertyMinProperties(d, p, _) => Step::typing("Edit min properties", "minProperties", &[*d, *p]),
+            Msg::UpdateObjectPropertyMaxProperties(d, p, _) => Step::typing("Edit max properties", "maxProperties", &[*d, *p]),
+
+            // Recursive properties
+            Msg::AddRecProperty(_, _) => Step::new("Add inner property"),
+            Msg::RemoveRecProperty(_, _, _) => Step::new("Remove inner property"),
+            Msg::DuplicateRecProperty(_, _, _) => Step::new("Duplicate inner property"),
+            Msg::UpdateRecPropertyType(_, _, _, _) => Step::new("Change inner property type"),
+            Msg::UpdateRecPropertyName(d, p, r, _) => Step::typing("Rename inner property", "inner property name", &[*d, *p, *r]),
+            Msg::UpdateRecPropertyRequired(_, _, _, _) => Step::new("Change required inner property"),
+            Msg::UpdateRecPropertyDescription(d, p, r, _) => Step::typing("Edit inner property description", "inner description", &[*d, *p, *r]),
+            Msg::UpdateRecPropertyComment(d, p, r, _) => Step::typing("Edit inner property comment", "inner comment", &[*d, *p, *r]),
+            Msg::UpdateStringRecPropertyMinLength(d, p, r, _) => Step::typing("Edit min length", "inner minLength", &[*d, *p, *r]),
+            Msg::UpdateStringRecPropertyMaxLength(d, p, r, _) => Step::typing("Edit max length", "inner maxLength", &[*d, *p, *r]),
+            Msg::UpdateStringRecPropertyPattern(d, p, r, _) => Step::typing("Edit pattern", "inner pattern", &[*d, *p, *r]),
+            Msg::UpdateStringRecPropertyFormat(d, p, r, _) => Step::typing("Edit format", "inner format", &[*d, *p, *r]),
+            Msg::UpdateIntegerRecPropertyMaximum(d, p, r, _) => Step::typing("Edit maximum", "inner maximum", &[*d, *p, *r]),
+            Msg::UpdateIntegerRecPropertyMinimum(d, p, r, _) => Step::typing("Edit minimum", "inner minimum", &[*d, *p, *r]),
+            Msg::UpdateArrayRecPropertyByteArray(_, _, _, _) => Step::new("Change byte array"),
+            Msg::UpdateArrayRecPropertyMinItems(d, p, r, _) => Step::typing("Edit min items", "inner minItems", &[*d, *p, *r]),
+            Msg::UpdateArrayRecPropertyMaxItems(d, p, r, _) => Step::typing("Edit max items", "inner maxItems", &[*d, *p, *r]),
+            Msg::UpdateArrayRecPropertyContentMediaType(d, p, r, _) => Step::typing("Edit content media type", "inner contentMediaType", &[*d, *p, *r]),
+            Msg::UpdateObjectRecPropertyMaxProperties(d, p, r, _) => Step::typing("Edit max properties", "inner maxProperties", &[*d, *p, *r]),
+            Msg::UpdateObjectRecPropertyMinProperties(d, p, r, _) => Step::typing("Edit min properties", "inner minProperties", &[*d, *p, *r]),
+
+            // Import
+            Msg::UpdateJsonEditor(_) => Step::typing("Edit JSON", "json", &[]),
+
+            // Messages that change the document types
+            Msg::LoadBaseContract => Step::new("Load base contract"),
+            Msg::ImportJsonSchema => Step::new("Import JSON Schema"),
+            Msg::ImportYaml => Step::new("Import YAML"),
+            Msg::FileLoaded(_, _) => Step::new("Import file"),
+            Msg::UseTemplate(_) | Msg::UseUserTemplate(_) => Step::new("Use template"),
+            Msg::ApplyBulkEntry => Step::new("Bulk add properties"),
+            Msg::AddSuggestedIndices => Step::new("Add suggested indices"),
+            Msg::UpdateReference(_, _, _) => Step::new("Change reference"),
+
+            // Messages that only change other state, which the history doesn't record
+            Msg::UpdateTransitionIdentityId(_)
+            | Msg::UpdateTransitionKeyId(_)
+            | Msg::UpdateTransitionPrivateKey(_)
+            | Msg::UpdateTransitionEncoding(_)
+            | Msg::BuildCreateTransition
+            | Msg::UpdateBaseContractJson(_)
+            | Msg::CheckUpdate
+            | Msg::BuildUpdateTransition
+            | Msg::UpdateCompareJson(_)
+            | Msg::Compare
+            | Msg::UpdateSampleDocumentType(_)
+            | Msg::UpdateSampleDocuments(_)
+            | Msg::ValidateSampleDocuments
+            | Msg::UpdateSampleMode(_)
+            | Msg::GenerateSampleDocuments
+            | Msg::UseGeneratedSamples
+            | Msg::UpdateExportFormat(_)
+            | Msg::Export
+            | Msg::UpdateJsonSchemaInput(_)
+            | Msg::UpdateYamlInput(_)
+            | Msg::Share
+            | Msg::OpenProject(_)
+            | Msg::UpdateProjectName(_)
+            | Msg::NewProject
+            | Msg::RenameProject
+            | Msg::DuplicateProject
+            | Msg::DeleteProject
+            | Msg::Undo
+            | Msg::Redo
+            | Msg::JumpToHistory(_)
+            | Msg::UploadFiles(_)
+            | Msg::Download(_)
+            | Msg::UpdateTemplateName(_)
+            | Msg::SaveTemplate
+            | Msg::DeleteUserTemplate(_)
+            | Msg::OpenBulkEntry(_)
+            | Msg::CloseBulkEntry
+            | Msg::UpdateBulkInput(_)
+            | Msg::UpdateNavigatorSearch(_)
+            | Msg::ToggleCollapsed(_)
+            | Msg::JumpTo(_, _)
+            | Msg::UpdateJsonFormat(_)
+            | Msg::ToggleJsonFold(_)
+            | Msg::SelectJsonNode(_)
+            | Msg::UpdateQueryDocumentType(_)
+            | Msg::UpdateQueryInput(_)
+            | Msg::PlanQuery
+            | Msg::UpdateIndexQueries(_)
+            | Msg::SuggestIndices => Step::new("Edit"),
+        }
+    }
+}
+
+/// Whether an event target is a text field or other editable element, where Ctrl+Z undoes typing
+pub fn is_text_field(target: Option<EventTarget>) -> bool {
+    let element = match target.and_then(|target| target.dyn_into::<HtmlElement>().ok()) {
+        Some(element) => element,
+        None => return false,
+    };
+    match element.tag_name().to_ascii_lowercase().as_str() {
+        "textarea" => true,
+        "input" => !matches!(element.get_attribute("type").unwrap_or_default().as_str(), "checkbox" | "radio" | "button" | "file"),
+        _ => element.is_content_editable(),
+    }
+}
+
+/// Linear history of states with a position. Undo moves back, redo moves forward and a new change
+/// drops the states after the position.
+pub struct History {
+    pub states: Vec<HistoryState>,
+    pub position: usize,
+}
+
+impl History {
+    pub fn new(document_types: &[DocumentType], references: &[Reference]) -> Self {
+        let state = HistoryState { label: String::from("Opened project"), group: None, document_types: document_types.to_vec(), references: references.to_vec() };
+        Self { states: vec![state], position: 0 }
+    }
+
+    /// Records the document types and references after a message if it changed them
+    pub fn record(&mut self, step: Step, document_types: &[DocumentType], references: &[Reference]) {
+        let current = &self.states[self.position];
+        if current.document_types == document_types && current.references == references {
+            return;
+        }
+        let is_last = self.position + 1 == self.states.len();
+        if is_last && self.position > 0 && step.group.is_some() && current.group == step.group {
+            self.states[self.position].document_types = document_types.to_vec();
+            self.states[self.position].references = references.to_vec();
+            return;
+        }
+        self.states.truncate(self.position + 1);
+        self.states.push(HistoryState { label: step.label, group: step.group, document_types: document_types.to_vec(), references: references.to_vec() });
+        if self.states.len() > MAX_STATES {
+            self.states.remove(0);
+        }
+        self.position = self.states.len() - 1;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position + 1 < self.states.len()
+    }
+}
+
+impl Model {
+    /// Restores the document types and references of a history state
+    pub fn jump_to_history(&mut self, position: usize) {
+        if position < self.history.states.len() {
+            self.history.position = position;
+            let old_names = self.document_types.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+            self.document_types = self.history.states[position].document_types.clone();
+            self.references = self.history.states[position].references.clone();
+            self.remap_document_types_by_name(&old_names);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.history.can_undo() {
+            self.jump_to_history(self.history.position - 1);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.history.can_redo() {
+            self.jump_to_history(self.history.position + 1);
+        }
+    }
+
+    pub fn view_history(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="input-fields">
+                <h2>{"History"}</h2>
+                <div>
+                    <button class="button2" title="Ctrl+Z" disabled={!self.history.can_undo()} onclick={ctx.link().callback(|_| Msg::Undo)}>{"Undo"}</button>
+                    <button class="button2" title="Ctrl+Shift+Z or Ctrl+Y" disabled={!self.history.can_redo()} onclick={ctx.link().callback(|_| Msg::Redo)}>{"Redo"}</button>
+                </div>
+                <ol class="history-list">
+                    {for self.history.states.iter().enumerate().map(|(i, state)| {
+                        let class = if i == self.history.position { "history-current" } else if i > self.history.position { "history-undone" } else { "" };
+                        html! {
+                            <li class={class} onclick={ctx.link().callback(move |_| Msg::JumpToHistory(i))}>{state.label.clone()}</li>
+                        }
+                    })}
+                </ol>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Index suggestions from the queries an app runs, and checks of the declared indices
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{query::{self, Query}, DocumentType, Index, IndexProperties, Model, Msg};
+
+/// Platform's limits per document type
+const MAX_INDICES: usize = 10;
+const MAX_UNIQUE_INDICES: usize = 3;
+const MAX_INDEX_PROPERTIES: usize = 10;
+
+/// A proposed index and the queries it serves, numbered from 1
+pub struct Suggestion {
+    pub index: Index,
+    pub queries: Vec<usize>,
+    /// Whether the document type already declares an index with these properties
+    pub declared: bool,
+}
+
+pub struct IndexReport {
+    /// Invalid queries and queries no index can serve
+    pub messages: Vec<String>,
+    /// The smallest set of indices found that serves every valid query
+    pub suggestions: Vec<Suggestion>,
+    /// Notes on each declared index: which queries it serves, and whether it is redundant
+    pub declared: Vec<(String, Vec<String>)>,
+    /// Platform limits the declared or suggested indices exceed, alone or once the suggestions are added
+    pub warnings: Vec<String>,
+}
+
+/// Splits the input into queries: a JSON array of queries, or one query per line
+fn split_queries(input: &str) -> Vec<String> {
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(queries)) => queries.iter().map(|q| q.to_string()).collect(),
+        _ => input.lines().filter(|line| !line.trim().is_empty()).map(|line| line.to_string()).collect(),
+    }
+}
+
+/// Index name from its properties, e.g. `ownerIdAndCreatedAt`
+fn index_name(properties: &[IndexProperties], taken: &[String]) -> String {
+    let words = properties
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let name = p.0.trim_start_matches('$').replace('.', "");
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(first) if i > 0 => format!("And{}{}", first.to_ascii_uppercase(), chars.as_str()),
+                _ => name,
+            }
+        })
+        .collect::<String>();
+    let name = if words.is_empty() { String::from("index") } else { words };
+    if !taken.contains(&name) {
+        return name;
+    }
+    (2..).map(|n| format!("{}{}", name, n)).find(|candidate| !taken.contains(candidate)).unwrap_or(name)
+}
+
+/// The index a query needs: == fields, most frequent across all queries first so indices share
+/// prefixes, then the in field, the range field and the rest of orderBy. Directions follow
+/// orderBy, flipped if needed so the first ordered field is ascending.
+fn ideal_index(query: &Query, frequency: &HashMap<String, usize>) -> Vec<IndexProperties> {
+    let flip = query.order_by.first().map_or(false, |(_, asc)| !asc);
+    let direction = |field: &str| {
+        let asc = query.order_by.iter().find(|(f, _)| f == field).map_or(true, |(_, asc)| *asc != flip);
+        String::from(if asc { "asc" } else { "desc" })
+    };
+    let mut equal = query.equal.clone();
+    equal.sort_by(|a, b| frequency.get(b).cmp(&frequency.get(a)).then(a.cmp(b)));
+    let mut fields = equal;
+    fields.extend(query.in_field.iter().cloned());
+    fields.extend(query.range.iter().cloned());
+    for (field, _) in &query.order_by {
+        if !fields.contains(field) {
+            fields.push(field.clone());
+        }
+    }
+    fields.iter().map(|field| IndexProperties(field.clone(), direction(field))).collect()
+}
+
+fn describe(index: &Index) -> String {
+    index.properties.iter().map(|p| format!("{} {}", p.0, p.1)).collect::<Vec<_>>().join(", ")
+}
+
+fn join_numbers(numbers: &[usize]) -> String {
+    numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Whether `a`'s properties are `b`'s, or `b`'s with every direction reversed
+fn same_properties(a: &[IndexProperties], b: &[IndexProperties]) -> bool {
+    let reversed = |p: &IndexProperties| IndexProperties(p.0.clone(), String::from(if p.1 == "asc" { "desc" } else { "asc" }));
+    a.len() == b.len() && (a.iter().zip(b).all(|(x, y)| x == y) || a.iter().zip(b).all(|(x, y)| reversed(x) == *y))
+}
+
+/// Checks the index limits for a set of indices
+fn limit_warnings(label: &str, indices: &[&Index]) -> Vec<String> {
+    let mut warnings = vec![];
+    if indices.len() > MAX_INDICES {
+        warnings.push(format!("{} has {} indices, more than the {} Platform allows per document type", label, indices.len(), MAX_INDICES));
+    }
+    let unique = indices.iter().filter(|index| index.unique).count();
+    if unique > MAX_UNIQUE_INDICES {
+        warnings.push(format!("{} has {} unique indices, more than the {} Platform allows per document type", label, unique, MAX_UNIQUE_INDICES));
+    }
+    for index in indices.iter().filter(|index| index.properties.len() > MAX_INDEX_PROPERTIES) {
+        warnings.push(format!("The index \"{}\" has {} properties, more than the {} Platform allows", index.name, index.properties.len(), MAX_INDEX_PROPERTIES));
+    }
+    warnings
+}
+
+/// The declared indices and the suggestions not declared yet, as "Add suggested indices" leaves them
+fn with_suggestions<'a>(document_type: &'a DocumentType, suggestions: &'a [Suggestion]) -> Vec<&'a Index> {
+    document_type.indices.iter().chain(suggestions.iter().filter(|s| !s.declared).map(|s| &s.index)).collect()
+}
+
+/// Why adding the suggestions would go over Platform's limits on indices and unique indices
+fn add_limit_errors(indices: &[&Index]) -> Vec<String> {
+    let mut errors = vec![];
+    if indices.len() > MAX_INDICES {
+        errors.push(format!("Adding the suggested indices would give the document type {} indices, more than the {} Platform allows", indices.len(), MAX_INDICES));
+    }
+    let unique = indices.iter().filter(|index| index.unique).count();
+    if unique > MAX_UNIQUE_INDICES {
+        errors.push(format!("Adding the suggested indices would give the document type {} unique indices, more than the {} Platform allows", unique, MAX_UNIQUE_INDICES));
+    }
+    errors
+}
+
+/// Suggests indices for the queries and reviews the declared ones
+pub fn suggest(input: &str, document_type: &DocumentType) -> IndexReport {
+    let mut messages = vec![];
+    let mut queries = vec![];
+    for (i, text) in split_queries(input).iter().enumerate() {
+        match query::parse_query(text, document_type) {
+            Ok(query) if query.uses_primary_key() => {}
+            Ok(query) => queries.push((i + 1, query)),
+            Err(errors) => messages.push(format!("Query {}: {}", i + 1, errors.join("; "))),
+        }
+    }
+
+    let mut frequency = HashMap::new();
+    for field in queries.iter().flat_map(|(_, q)| q.equal.iter()) {
+        *frequency.entry(field.clone()).or_insert(0) += 1;
+    }
+    // Queries needing the most fields go first, so shorter queries can reuse their indices as prefixes
+    let mut ordered = queries.iter().collect::<Vec<_>>();
+    ordered.sort_by_key(|(_, q)| std::cmp::Reverse(q.equal.len() + q.in_field.iter().len() + q.range.iter().len() + q.order_by.len()));
+    let mut indices: Vec<Index> = vec![];
+    let mut servable = vec![];
+    for (number, query) in ordered {
+        if indices.iter().any(|index| query::check_index(index, query).is_empty()) {
+            servable.push((*number, query));
+            continue;
+        }
+        let index = Index { name: String::new(), properties: ideal_index(query, &frequency), unique: false };
+        match query::check_index(&index, query).first() {
+            Some(reason) => messages.push(format!("Query {}: no index can serve it: {}", number, reason)),
+            None => {
+                indices.push(index);
+                servable.push((*number, query));
+            }
+        }
+    }
+    // Drop indices whose queries are all served by the others
+    let mut i = 0;
+    while i < indices.len() {
+        let redundant = servable.iter().all(|(_, query)| {
+            !query::check_index(&indices[i], query).is_empty() || indices.iter().enumerate().any(|(j, other)| j != i && query::check_index(other, query).is_empty())
+        });
+        if redundant {
+            indices.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    let mut taken = document_type.indices.iter().map(|index| index.name.clone()).collect::<Vec<_>>();
+    let mut suggestions = vec![];
+    for mut index in indices {
+        let existing = document_type.indices.iter().find(|declared| same_properties(&declared.properties, &index.properties));
+        match existing {
+            Some(declared) => index.name = declared.name.clone(),
+            None => {
+                index.name = index_name(&index.properties, &taken);
+                taken.push(index.name.clone());
+            }
+        }
+        let mut served = servable.iter().filter(|(_, query)| query::check_index(&index, query).is_empty()).map(|(number, _)| *number).collect::<Vec<_>>();
+        served.sort();
+        suggestions.push(Suggestion { index, queries: served, declared: existing.is_some() });
+    }
+
+    let declared = document_type
+        .indices
+        .iter()
+        .enumerate()
+        .map(|(i, index)| {
+            let mut notes = vec![];
+            let mut served = queries.iter().filter(|(_, query)| query::check_index(index, query).is_empty()).map(|(number, _)| *number).collect::<Vec<_>>();
+            served.sort();
+            if served.is_empty() {
+                notes.push(String::from("Serves none of the listed queries"));
+            } else {
+                notes.push(format!("Serves queries {}", join_numbers(&served)));
+            }
+            for (j, other) in document_type.indices.iter().enumerate().filter(|(j, _)| *j != i) {
+                let uniqueness = if index.unique { ", but it is still needed for its unique constraint" } else { "" };
+                if same_properties(&index.properties, &other.properties) {
+                    if j > i {
+                        notes.push(format!("Duplicates \"{}\"{}", other.name, uniqueness));
+                    }
+                } else if other.properties.len() > index.properties.len()
+                    && same_properties(&index.properties, &other.properties[..index.properties.len()])
+                    && queries.iter().filter(|(number, _)| served.contains(number)).all(|(_, query)| query::check_index(other, query).is_empty())
+                {
+                    notes.push(format!("Is a prefix of \"{}\", which serves every listed query this one does{}", other.name, uniqueness));
+                }
+            }
+            (index.name.clone(), notes)
+        })
+        .collect();
+
+    let mut warnings = limit_warnings("The document type", &document_type.indices.iter().collect::<Vec<_>>());
+    warnings.extend(limit_warnings("The suggested set", &suggestions.iter().map(|s| &s.index).collect::<Vec<_>>()));
+    warnings.extend(add_limit_errors(&with_suggestions(document_type, &suggestions)));
+    let unserved = queries.iter().filter(|(_, query)| !document_type.indices.iter().any(|index| query::check_index(index, query).is_empty())).map(|(number, _)| *number).collect::<Vec<_>>();
+    if !unserved.is_empty() {
+        messages.push(format!("No declared index serves queries {}", join_numbers(&unserved)));
+    }
+    IndexReport { messages, suggestions, declared, warnings }
+}
+
+impl Model {
+    pub fn suggest_indices(&mut self) {
+        let name = self.selected_query_document_type();
+        self.index_report = self.document_types.iter().find(|d| d.name == name).map(|document_type| suggest(&self.index_queries_input, document_type));
+    }
+
+    /// Adds the suggested indices the document type doesn't declare yet, unless that goes over
+    /// Platform's limits
+    pub fn add_suggested_indices(&mut self) {
+        let name = self.selected_query_document_type();
+        let mut errors = vec![];
+        if let Some(document_type) = self.document_types.iter_mut().find(|d| d.name == name) {
+            let report = suggest(&self.index_queries_input, document_type);
+            errors = add_limit_errors(&with_suggestions(document_type, &report.suggestions));
+            if errors.is_empty() {
+                document_type.indices.extend(report.suggestions.into_iter().filter(|s| !s.declared).map(|s| s.index));
+            }
+        }
+        self.suggest_indices();
+        if let Some(report) = self.index_report.as_mut().filter(|_| !errors.is_empty()) {
+            report.messages.insert(0, String::from("The suggested indices weren't added"));
+        }
+    }
+
+    pub fn view_index_suggestions(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <>
+            <h3>{"Index suggestions"}</h3>
+            <textarea class="textarea" placeholder="The queries your app runs on this document type, one JSON query per line or a JSON array" value={self.index_queries_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateIndexQueries(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+            <div><button class="button" onclick={ctx.link().callback(|_| Msg::SuggestIndices)}>{"Suggest indices"}</button></div>
+            {for self.index_report.iter().map(|report| html! {
+                <>
+                <ul class="error-text">
+                    { for report.warnings.iter().chain(&report.messages).map(|i| html! { <li>{i.clone()}</li> }) }
+                </ul>
+                <h3>{"Suggested indices"}</h3>
+                <ul>
+                    {for report.suggestions.iter().map(|suggestion| html! {
+                        <li><b>{suggestion.index.name.clone()}</b>{format!(": {} — serves queries {}{}", describe(&suggestion.index), join_numbers(&suggestion.queries), if suggestion.declared { " (already declared)" } else { "" })}</li>
+                    })}
+                </ul>
+                {
+                    if report.suggestions.iter().all(|s| s.declared) {
+                        html! {}
+                    } else {
+                        html! { <div><button class="button2" onclick={ctx.link().callback(|_| Msg::AddSuggestedIndices)}>{"Add suggested indices"}</button></div> }
+                    }
+                }
+                {
+                    if report.declared.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <>
+                            <h3>{"Declared indices"}</h3>
+                            <ul>
+                                {for report.declared.iter().map(|(name, notes)| html! {
+                                    <li><b>{name.clone()}</b>{format!(": {}", notes.join(". "))}</li>
+                                })}
+                            </ul>
+                            </>
+                        }
+                    }
+                }
+                </>
+            })}
+            </>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataType, Property};
+
+    fn index(name: &str, properties: &[(&str, &str)]) -> Index {
+        Index { name: name.to_string(), properties: properties.iter().map(|(name, direction)| IndexProperties(name.to_string(), direction.to_string())).collect(), unique: false }
+    }
+
+    fn document_type(indices: Vec<Index>) -> DocumentType {
+        let property = |name: &str, data_type: DataType| Property { name: name.to_string(), data_type, ..Default::default() };
+        DocumentType {
+            name: String::from("note"),
+            properties: vec![property("title", DataType::String), property("score", DataType::Integer)],
+            indices,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ideal_index_puts_frequent_equal_fields_first_and_follows_order_by() {
+        let query = query::parse_query(
+            r#"{"where": [["title", "==", "a"], ["$ownerId", "==", "x"], ["score", ">", 1]], "orderBy": [["score", "desc"], ["$createdAt", "asc"]]}"#,
+            &document_type(vec![]),
+        )
+        .unwrap();
+        let frequency = HashMap::from([(String::from("$ownerId"), 2), (String::from("title"), 1)]);
+        // The first orderBy field is descending, so every direction is flipped
+        assert_eq!(ideal_index(&query, &frequency), index("", &[("$ownerId", "asc"), ("title", "asc"), ("score", "asc"), ("$createdAt", "desc")]).properties);
+    }
+
+    #[test]
+    fn suggest_shares_one_index_between_prefix_queries() {
+        let input = "{\"where\": [[\"$ownerId\", \"==\", \"x\"]], \"orderBy\": [[\"$createdAt\", \"asc\"]]}\n{\"where\": [[\"$ownerId\", \"==\", \"x\"]]}";
+        let report = suggest(input, &document_type(vec![]));
+        assert_eq!(report.suggestions.len(), 1);
+        let suggestion = &report.suggestions[0];
+        assert_eq!(suggestion.index, index("ownerIdAndCreatedAt", &[("$ownerId", "asc"), ("$createdAt", "asc")]));
+        assert_eq!(suggestion.queries, [1, 2]);
+        assert!(!suggestion.declared);
+        assert_eq!(report.messages, ["No declared index serves queries 1, 2"]);
+    }
+
+    #[test]
+    fn suggest_reports_invalid_queries_and_skips_primary_key_lookups() {
+        let report = suggest(r#"[{"where": [["body", "==", "x"]]}, {"where": [["$id", "==", "x"]]}]"#, &document_type(vec![]));
+        assert_eq!(report.messages, ["Query 1: \"body\" is not a property of the document type"]);
+        assert!(report.suggestions.is_empty());
+    }
+
+    #[test]
+    fn suggest_reviews_declared_indices() {
+        let declared = vec![
+            index("byTitle", &[("title", "asc")]),
+            index("byTitleAndScore", &[("title", "asc"), ("score", "asc")]),
+            index("byTitleAndScoreAgain", &[("title", "desc"), ("score", "desc")]),
+        ];
+        let report = suggest(r#"{"where": [["title", "==", "a"]]}"#, &document_type(declared));
+        assert_eq!(report.declared[0], (String::from("byTitle"), vec![
+            String::from("Serves queries 1"),
+            String::from("Is a prefix of \"byTitleAndScore\", which serves every listed query this one does"),
+            String::from("Is a prefix of \"byTitleAndScoreAgain\", which serves every listed query this one does"),
+        ]));
+        assert_eq!(report.declared[1].1, ["Serves queries 1", "Duplicates \"byTitleAndScoreAgain\""]);
+        assert_eq!(report.declared[2].1, ["Serves queries 1"]);
+        assert!(report.suggestions[0].declared);
+        assert!(report.messages.is_empty());
+    }
+
+    #[test]
+    fn suggest_warns_over_platform_limits() {
+        let declared = (0..11).map(|i| index(&format!("index{}", i), &[("title", "asc")])).collect();
+        let report = suggest("", &document_type(declared));
+        assert_eq!(report.warnings[0], "The document type has 11 indices, more than the 10 Platform allows per document type");
+    }
+}
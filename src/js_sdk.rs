@@ -0,0 +1,160 @@
+//! Dash JS SDK script that registers the contract and uses its document types
+
+use serde_json::{json, Value};
+
+use crate::{
+    diff::index_property_names,
+    export::pascal_case,
+    samples::{generate_samples, SampleMode},
+    typescript::property_key,
+    is_identifier,
+};
+
+/// Name the contract is registered under in the client's `apps`
+const APP_NAME: &str = "contract";
+
+const HEADER: &str = r#"// Generated by Data Contract Creator
+// Requires the Dash JS SDK: npm install dash
+const Dash = require('dash');
+
+const { Identifier } = Dash.PlatformProtocol;
+
+const IDENTITY_ID = 'YOUR_IDENTITY_ID';
+
+const client = new Dash.Client({
+  network: 'testnet',
+  wallet: {
+    mnemonic: 'YOUR_MNEMONIC',
+    unsafeOptions: { skipSynchronizationBeforeHeight: 875000 },
+  },
+  apps: {
+    // Set the contract ID once the contract is registered to create and query documents
+    contract: { contractId: 'YOUR_CONTRACT_ID' },
+  },
+});
+"#;
+
+const FOOTER: &str = r#"registerContract()
+  .then((contract) => console.log('Contract registered:\n', contract.toJSON()))
+  .catch((e) => console.error('Something went wrong:\n', e))
+  .finally(() => client.disconnect());
+"#;
+
+/// Generates a script that registers the contract, with create and query functions for each document type
+pub fn generate(documents: &Value) -> String {
+    let mut sections = vec![HEADER.to_string()];
+    let documents_literal = serde_json::to_string_pretty(documents).unwrap_or_default();
+    sections.push(format!(
+        r#"const registerContract = async () => {{
+  const {{ platform }} = client;
+  const identity = await platform.identities.get(IDENTITY_ID);
+  const documents = {};
+  const contract = await platform.contracts.create(documents, identity);
+  const validationResult = await platform.dpp.dataContract.validate(contract);
+  if (!validationResult.isValid()) {{
+    throw validationResult.errors[0];
+  }}
+  await platform.contracts.publish(contract, identity);
+  return contract;
+}};
+"#,
+        indent(&documents_literal, "  ")
+    ));
+    if let Some(documents) = documents.as_object() {
+        for (name, schema) in documents {
+            sections.push(create_function(name, schema));
+            sections.push(query_function(name, schema));
+        }
+    }
+    sections.push(FOOTER.to_string());
+    sections.join("\n")
+}
+
+/// Function that creates and broadcasts one document of a document type, filled with a sample document
+fn create_function(name: &str, schema: &Value) -> String {
+    let document = generate_samples(schema, SampleMode::Valid).into_iter().next().map(|sample| sample.document).unwrap_or_else(|| json!({}));
+    format!(
+        r#"// Creates a `{name}` document
+const create{function} = async () => {{
+  const {{ platform }} = client;
+  const identity = await platform.identities.get(IDENTITY_ID);
+  const document = await platform.documents.create('{app}.{name}', identity, {document});
+  await platform.documents.broadcast({{ create: [document] }}, identity);
+  return document;
+}};
+"#,
+        name = name,
+        function = pascal_case(name),
+        app = APP_NAME,
+        document = js_value(&document, schema, "  "),
+    )
+}
+
+/// Function that queries documents of a document type, filtered and ordered by its first index when it has one
+fn query_function(name: &str, schema: &Value) -> String {
+    let mut options = Vec::new();
+    let first_index = schema.get("indices").and_then(|i| i.as_array()).and_then(|indices| indices.first());
+    if let Some(property) = first_index.and_then(|index| index_property_names(index).into_iter().next()) {
+        let value = if property == "$ownerId" {
+            Some(String::from("IDENTITY_ID"))
+        } else if property.starts_with('$') {
+            None
+        } else {
+            let document = generate_samples(schema, SampleMode::Valid).into_iter().next().map(|sample| sample.document).unwrap_or_else(|| json!({}));
+            document.get(&property).map(|value| js_value(value, &schema["properties"][&property], "    "))
+        };
+        if let Some(value) = value {
+            options.push(format!("where: [[{}, '==', {}]]", Value::String(property.clone()), value));
+        }
+        options.push(format!("orderBy: [[{}, 'asc']]", Value::String(property)));
+    }
+    options.push(String::from("limit: 10"));
+    format!(
+        r#"// Queries `{name}` documents
+const query{function} = async () => {{
+  const documents = await client.platform.documents.get('{app}.{name}', {{
+    {options},
+  }});
+  return documents.map((document) => document.toJSON());
+}};
+"#,
+        name = name,
+        function = pascal_case(name),
+        app = APP_NAME,
+        options = options.join(",\n    "),
+    )
+}
+
+/// Renders a sample value as a JavaScript literal. Byte arrays arrive in their JSON form and become
+/// `Identifier` or `Buffer` objects as the SDK expects.
+fn js_value(value: &Value, schema: &Value, indent: &str) -> String {
+    if schema.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+        if let Some(encoded) = value.as_str() {
+            return if is_identifier(schema) {
+                format!("Identifier.from({})", Value::String(encoded.to_string()))
+            } else {
+                format!("Buffer.from({}, 'base64')", Value::String(encoded.to_string()))
+            };
+        }
+    }
+    let inner = format!("{}  ", indent);
+    match value {
+        Value::Object(obj) if !obj.is_empty() => {
+            let fields = obj
+                .iter()
+                .map(|(key, field)| format!("{}{}: {},\n", inner, property_key(key), js_value(field, &schema["properties"][key], &inner)))
+                .collect::<String>();
+            format!("{{\n{}{}}}", fields, indent)
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let items = items.iter().map(|item| format!("{}{},\n", inner, js_value(item, &schema["items"], &inner))).collect::<String>();
+            format!("[\n{}{}]", items, indent)
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Indents every line but the first, for inlining multi-line text at an indented position
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(&format!("\n{}", prefix))
+}
@@ -0,0 +1,106 @@
+//! Two-way JSON editor: form edits rewrite the JSON and valid JSON edits rewrite the form
+
+use serde_json::{Map, Value};
+
+use crate::{diff::{documents_of, pointer_segment}, Model};
+
+/// Reads the document schemas from the editor text, either bare or inside a full contract
+fn parse_documents(text: &str) -> Result<Map<String, Value>, String> {
+    if text.trim().is_empty() {
+        return Err(String::from("Type or paste the document schemas or a full contract"));
+    }
+    let value: Value = serde_json::from_str(text).map_err(|e| {
+        // serde_json appends the position to its message, so report it up front instead
+        let message = e.to_string();
+        let message = message.split(" at line ").next().unwrap_or_default().to_string();
+        format!("Line {}, column {}: {}", e.line(), e.column(), message)
+    })?;
+    documents_of(&value)
+        .or_else(|| value.as_object())
+        .cloned()
+        .ok_or_else(|| String::from("The contract must be a JSON object"))
+}
+
+/// Lists what the form lost when it read `imported` and generated `generated` from it, as JSON
+/// pointers. Empty arrays, `"unique": false` and the order of required fields don't count, since
+/// the form leaves them out or reorders them without changing the schema.
+pub fn dropped_keywords(path: &str, imported: &Value, generated: &Value, notes: &mut Vec<String>) {
+    match (imported, generated) {
+        (Value::Object(imported_obj), Value::Object(generated_obj)) => {
+            for (key, value) in imported_obj {
+                let key_path = format!("{}/{}", path, pointer_segment(key));
+                match generated_obj.get(key) {
+                    Some(generated_value) => dropped_keywords(&key_path, value, generated_value, notes),
+                    None if value.as_array().map_or(false, |a| a.is_empty()) || (key == "unique" && *value == Value::Bool(false)) => {}
+                    None => notes.push(format!("{} was left out: the form can't keep it", key_path)),
+                }
+            }
+        }
+        (Value::Array(imported_arr), Value::Array(generated_arr)) if path.ends_with("/required") => {
+            for field in imported_arr.iter().filter(|field| !generated_arr.contains(field)) {
+                notes.push(format!("{} {} was left out: the form can't keep it", path, field));
+            }
+        }
+        (Value::Array(imported_arr), Value::Array(generated_arr)) => {
+            for (i, value) in imported_arr.iter().enumerate() {
+                match generated_arr.get(i) {
+                    Some(generated_value) => dropped_keywords(&format!("{}/{}", path, i), value, generated_value, notes),
+                    None => notes.push(format!("{}/{} was left out: the form can't keep it", path, i)),
+                }
+            }
+        }
+        _ if imported != generated => notes.push(format!("{} was changed from {} to {}: the form can't keep the original value", path, imported, generated)),
+        _ => {}
+    }
+}
+
+impl Model {
+    /// The document schemas generated from the form, pretty-printed
+    fn form_json(&mut self) -> String {
+        self.json_object = self.generate_json_object();
+        serde_json::to_string_pretty(&self.documents_json()).unwrap_or_default()
+    }
+
+    /// Rewrites the editor after a form change. Updates that leave the form as it was keep the
+    /// editor text, so an unfinished edit with a syntax error isn't lost.
+    pub fn sync_json_editor(&mut self) {
+        let json = self.form_json();
+        if json != self.json_editor_synced {
+            self.json_editor = json.clone();
+            self.json_editor_synced = json;
+            // What the form couldn't keep from the last JSON edit is gone from the text now
+            self.json_editor_errors = std::mem::take(&mut self.json_editor_dropped)
+                .into_iter()
+                .map(|note| format!("Removed from the JSON: {}", note))
+                .collect();
+        }
+    }
+
+    /// Shows saved editor text, which may hold an unfinished edit, without the next sync replacing it
+    pub fn restore_json_editor(&mut self, text: String) {
+        self.json_editor_synced = self.form_json();
+        self.json_editor = if text.is_empty() { self.json_editor_synced.clone() } else { text };
+        self.json_editor_errors = parse_documents(&self.json_editor).err().into_iter().collect();
+        self.json_editor_dropped = vec![];
+    }
+
+    /// Applies the editor text to the form if it parses and the form can read it, otherwise
+    /// reports why not. Keywords the form doesn't keep stay in the text until the next form change.
+    pub fn edit_json(&mut self, text: String) {
+        self.json_editor = text;
+        let imported = parse_documents(&self.json_editor).map_err(|message| vec![message]).and_then(|documents| {
+            self.imported_json = Value::Object(documents).to_string();
+            self.parse_imported_json()
+        });
+        match imported {
+            Ok(dropped) => {
+                self.json_editor_errors = dropped.clone();
+                self.json_editor_dropped = dropped;
+                self.json_editor_synced = self.form_json();
+            }
+            Err(errors) => {
+                self.json_editor_errors = errors;
+            }
+        }
+    }
+}
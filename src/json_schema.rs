@@ -0,0 +1,303 @@
+//! Import from and export to standard JSON Schema (draft-07 and 2020-12)
+
+use serde_json::{json, Map, Value};
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{diff::pointer_segment, Model, Msg};
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Keywords DPP accepts in document schemas, copied over as they are
+const SUPPORTED_KEYWORDS: [&str; 22] = [
+    "description", "$comment", "enum", "const", "required", "minLength", "maxLength", "pattern", "format", "contentMediaType",
+    "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf", "minItems", "maxItems", "uniqueItems",
+    "minProperties", "maxProperties", "byteArray", "contentEncoding",
+];
+
+/// Annotations DPP doesn't accept. They carry no validation, so they are dropped with a note.
+const ANNOTATION_KEYWORDS: [&str; 7] = ["title", "default", "examples", "deprecated", "readOnly", "writeOnly", "$anchor"];
+
+/// Keywords that only make sense at the root of a schema file
+const ROOT_KEYWORDS: [&str; 4] = ["$schema", "$id", "$defs", "definitions"];
+
+/// Dash-specific keywords removed from the standard export
+const DASH_KEYWORDS: [&str; 10] = [
+    "indices", "byteArray", "position", "documentsMutable", "documentsKeepHistory", "canBeDeleted", "transient",
+    "signatureSecurityLevelRequirement", "requiresIdentityEncryptionBoundedKey", "requiresIdentityDecryptionBoundedKey",
+];
+
+/// Result of importing a JSON Schema file
+pub struct SchemaImport {
+    /// The document types, ready for the contract's documents
+    pub documents: Map<String, Value>,
+    /// Everything that was changed or dropped because DPP doesn't support it
+    pub messages: Vec<String>,
+}
+
+/// Converts schemas to DPP document schemas, inlining local `$ref`s and collecting what had to change
+struct Importer<'a> {
+    root: &'a Value,
+    /// `$ref`s being inlined, to detect recursive schemas
+    ref_stack: Vec<String>,
+    messages: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn convert(&mut self, schema: &Value, path: &str) -> Value {
+        let schema_obj = match schema.as_object() {
+            Some(obj) => obj,
+            None => {
+                self.messages.push(format!("{}: boolean schemas are not supported and were replaced with an empty object", display_path(path)));
+                return json!({ "type": "object", "properties": {}, "additionalProperties": false });
+            }
+        };
+        if let Some(reference) = schema_obj.get("$ref").and_then(|r| r.as_str()) {
+            return self.inline_ref(reference, path);
+        }
+
+        let mut converted = Map::new();
+        for (keyword, value) in schema_obj {
+            let keyword_path = format!("{}/{}", path, pointer_segment(keyword));
+            match keyword.as_str() {
+                "type" => {
+                    if let Some(data_type) = self.convert_type(value, &keyword_path) {
+                        converted.insert(keyword.clone(), json!(data_type));
+                    }
+                }
+                "properties" => {
+                    let properties = value
+                        .as_object()
+                        .map(|props| {
+                            props.iter()
+                                .map(|(name, property)| (name.clone(), self.convert(property, &format!("{}/{}", keyword_path, pointer_segment(name)))))
+                                .collect::<Map<_, _>>()
+                        })
+                        .unwrap_or_default();
+                    converted.insert(keyword.clone(), Value::Object(properties));
+                }
+                "items" => {
+                    if value.is_array() {
+                        self.messages.push(format!("{}: tuple validation with an array of schemas is not supported by DPP and was dropped", display_path(&keyword_path)));
+                    } else {
+                        converted.insert(keyword.clone(), self.convert(value, &keyword_path));
+                    }
+                }
+                "additionalProperties" => {
+                    if value != &json!(false) {
+                        self.messages.push(format!("{}: DPP requires additionalProperties to be false; it was set to false", display_path(&keyword_path)));
+                    }
+                }
+                _ if SUPPORTED_KEYWORDS.contains(&keyword.as_str()) => {
+                    converted.insert(keyword.clone(), value.clone());
+                }
+                _ if ANNOTATION_KEYWORDS.contains(&keyword.as_str()) => {
+                    self.messages.push(format!("{}: the {} annotation is not supported by DPP and was dropped", display_path(&keyword_path), keyword));
+                }
+                _ if path.is_empty() && ROOT_KEYWORDS.contains(&keyword.as_str()) => {}
+                _ => {
+                    self.messages.push(format!("{}: {} is not supported by DPP and was dropped", display_path(&keyword_path), keyword));
+                }
+            }
+        }
+        if converted.get("type").and_then(|t| t.as_str()) == Some("object") || converted.contains_key("properties") {
+            converted.insert(String::from("type"), json!("object"));
+            converted.entry("properties").or_insert_with(|| json!({}));
+            converted.insert(String::from("additionalProperties"), json!(false));
+        }
+        Value::Object(converted)
+    }
+
+    /// Keeps a single type. DPP has no union or nullable types.
+    fn convert_type(&mut self, value: &Value, path: &str) -> Option<String> {
+        match value {
+            Value::String(data_type) if data_type == "null" => {
+                self.messages.push(format!("{}: the null type is not supported by DPP and was dropped", display_path(path)));
+                None
+            }
+            Value::String(data_type) => Some(data_type.clone()),
+            Value::Array(types) => {
+                let non_null = types.iter().filter_map(|t| t.as_str()).filter(|t| *t != "null").collect::<Vec<_>>();
+                let kept = non_null.first().map(|t| t.to_string());
+                match &kept {
+                    Some(data_type) => self.messages.push(format!(
+                        "{}: union type {} is not supported by DPP; only \"{}\" was kept",
+                        display_path(path),
+                        value,
+                        data_type
+                    )),
+                    None => self.messages.push(format!("{}: union type {} is not supported by DPP and was dropped", display_path(path), value)),
+                }
+                kept
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces a local `$ref` with the converted schema it points to
+    fn inline_ref(&mut self, reference: &str, path: &str) -> Value {
+        let target = reference.strip_prefix('#').and_then(|pointer| self.root.pointer(pointer));
+        match target {
+            Some(target) if !self.ref_stack.iter().any(|r| r == reference) => {
+                self.ref_stack.push(reference.to_string());
+                let converted = self.convert(target, path);
+                self.ref_stack.pop();
+                converted
+            }
+            Some(_) => {
+                self.messages.push(format!("{}: recursive $ref \"{}\" is not supported by DPP and was dropped", display_path(path), reference));
+                json!({ "type": "object", "properties": {}, "additionalProperties": false })
+            }
+            None => {
+                self.messages.push(format!("{}: $ref \"{}\" could not be resolved; only local references are supported", display_path(path), reference));
+                json!({ "type": "object", "properties": {}, "additionalProperties": false })
+            }
+        }
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+fn is_object_schema(schema: &Value) -> bool {
+    schema.get("type").and_then(|t| t.as_str()) == Some("object") || schema.get("properties").is_some()
+}
+
+/// Turns a JSON Schema file into document types. Every object schema under `$defs` or `definitions` becomes a
+/// document type, and so does the root schema when it has properties of its own.
+pub fn import(input: &str) -> Result<SchemaImport, String> {
+    let root: Value = serde_json::from_str(input).map_err(|e| format!("Invalid JSON: {}", e))?;
+    if !root.is_object() {
+        return Err(String::from("The JSON Schema must be an object"));
+    }
+    let mut importer = Importer { root: &root, ref_stack: vec![], messages: vec![] };
+    match root.get("$schema").and_then(|s| s.as_str()) {
+        Some(draft) if draft.contains("draft-07") || draft.contains("2020-12") => {}
+        Some(draft) => importer.messages.push(format!("/$schema: \"{}\" is neither draft-07 nor 2020-12; the schema was read as 2020-12", draft)),
+        None => {}
+    }
+
+    let mut documents = Map::new();
+    for defs_keyword in ["$defs", "definitions"] {
+        if let Some(defs) = root.get(defs_keyword).and_then(|d| d.as_object()) {
+            for (name, schema) in defs {
+                let path = format!("/{}/{}", defs_keyword, pointer_segment(name));
+                if is_object_schema(schema) {
+                    importer.ref_stack.push(format!("#{}", path));
+                    let converted = importer.convert(schema, &path);
+                    importer.ref_stack.pop();
+                    documents.insert(name.clone(), converted);
+                } else {
+                    importer.messages.push(format!("{}: only object schemas can be document types; it is only inlined where referenced", path));
+                }
+            }
+        }
+    }
+    if is_object_schema(&root) && root.get("properties").and_then(|p| p.as_object()).map_or(false, |p| !p.is_empty()) {
+        let name = root.get("title").and_then(|t| t.as_str()).map(document_type_name).unwrap_or_else(|| String::from("document"));
+        let converted = importer.convert(&root, "");
+        documents.insert(name, converted);
+    }
+    if documents.is_empty() {
+        return Err(String::from("The JSON Schema contains no object schemas to turn into document types"));
+    }
+    Ok(SchemaImport { documents, messages: importer.messages })
+}
+
+/// Converts a title such as "Contact Request" to a document type name such as `contactRequest`
+fn document_type_name(title: &str) -> String {
+    let mut name = String::new();
+    for (i, word) in title.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).enumerate() {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(if i == 0 { first.to_ascii_lowercase() } else { first.to_ascii_uppercase() });
+            name.push_str(chars.as_str());
+        }
+    }
+    if name.is_empty() { String::from("document") } else { name }
+}
+
+/// Removes the Dash-specific keywords from a document schema. Byte arrays become arrays of bytes.
+fn strip_dash_keywords(schema: &Value) -> Value {
+    let schema_obj = match schema.as_object() {
+        Some(obj) => obj,
+        None => return schema.clone(),
+    };
+    let mut stripped = Map::new();
+    for (keyword, value) in schema_obj {
+        match keyword.as_str() {
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .map(|props| props.iter().map(|(name, property)| (name.clone(), strip_dash_keywords(property))).collect::<Map<_, _>>())
+                    .unwrap_or_default();
+                stripped.insert(keyword.clone(), Value::Object(properties));
+            }
+            "items" => {
+                stripped.insert(keyword.clone(), strip_dash_keywords(value));
+            }
+            _ if DASH_KEYWORDS.contains(&keyword.as_str()) => {}
+            _ => {
+                stripped.insert(keyword.clone(), value.clone());
+            }
+        }
+    }
+    if schema.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+        stripped.insert(String::from("items"), json!({ "type": "integer", "minimum": 0, "maximum": 255 }));
+    }
+    Value::Object(stripped)
+}
+
+/// Generates a draft 2020-12 schema with each document type under `$defs`
+pub fn export(documents: &Value) -> String {
+    let defs = documents
+        .as_object()
+        .map(|docs| docs.iter().map(|(name, schema)| (name.clone(), strip_dash_keywords(schema))).collect::<Map<_, _>>())
+        .unwrap_or_default();
+    let schema = json!({
+        "$schema": DRAFT_2020_12,
+        "$defs": defs,
+    });
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+impl Model {
+    pub fn import_json_schema(&mut self) {
+        match import(&self.json_schema_input) {
+            Ok(result) => {
+                self.imported_json = Value::Object(result.documents).to_string();
+                self.json_schema_messages = match self.parse_imported_json() {
+                    Ok(dropped) => result.messages.into_iter().chain(dropped).collect(),
+                    Err(errors) => errors,
+                };
+            }
+            Err(message) => {
+                self.json_schema_messages = vec![message];
+            }
+        }
+    }
+
+    pub fn view_json_schema_import(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <p class="output-container">
+                <h2>{"Import JSON Schema"}</h2>
+                <textarea class="textarea" placeholder="Paste a draft-07 or 2020-12 JSON Schema" value={self.json_schema_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateJsonSchemaInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::ImportJsonSchema)}>{"Import JSON Schema"}</button></div>
+                {
+                    if self.json_schema_messages.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <>
+                            <h3>{"Import notes"}</h3>
+                            <ul class="error-text">
+                                { for self.json_schema_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                            </ul>
+                            </>
+                        }
+                    }
+                }
+            </p>
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Read-only code view of the contract with highlighting, folding and line numbers
+
+use serde_json::{Map, Value};
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, MouseEvent, TargetCast};
+
+use crate::{navigator, Model, Msg};
+
+/// How the code view lays out the JSON
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JsonFormat {
+    #[default]
+    Pretty,
+    Minified,
+    /// Pretty-printed with the keys of every object sorted
+    Canonical,
+}
+
+impl JsonFormat {
+    pub const ALL: [JsonFormat; 3] = [JsonFormat::Pretty, JsonFormat::Minified, JsonFormat::Canonical];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JsonFormat::Pretty => "Pretty",
+            JsonFormat::Minified => "Minified",
+            JsonFormat::Canonical => "Canonical (sorted keys)",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL.into_iter().find(|format| format.label() == label).unwrap_or_default()
+    }
+}
+
+/// Copies a value with the keys of every object sorted
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys = map.keys().collect::<Vec<_>>();
+            keys.sort();
+            Value::Object(keys.into_iter().map(|key| (key.clone(), sort_keys(&map[key]))).collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// A scalar with the class that colors it
+fn scalar(value: &Value) -> Html {
+    let class = match value {
+        Value::String(_) => "json-string",
+        Value::Number(_) => "json-number",
+        Value::Bool(_) => "json-bool",
+        _ => "json-null",
+    };
+    html! { <span class={class}>{value.to_string()}</span> }
+}
+
+fn key(name: &str) -> Html {
+    html! { <><span class="json-key">{Value::String(name.to_string()).to_string()}</span><span class="json-punct">{": "}</span></> }
+}
+
+/// The children of an object or array with the path segment of each
+fn children(value: &Value) -> Vec<(Option<&str>, String, &Value)> {
+    match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (Some(k.as_str()), k.clone(), v)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(i, v)| (None, i.to_string(), v)).collect(),
+        _ => vec![],
+    }
+}
+
+fn brackets(value: &Value) -> (&'static str, &'static str) {
+    if value.is_array() { ("[", "]") } else { ("{", "}") }
+}
+
+impl Model {
+    /// The form element a JSON path belongs to: the deepest property, nested property or index
+    /// on the path, or else its document type
+    fn form_element(&self, path: &[String]) -> Option<(usize, String)> {
+        let doc_index = self.document_types.iter().position(|d| Some(&d.name) == path.first())?;
+        let document_type = &self.document_types[doc_index];
+        let rest = path.iter().skip(1).map(|s| s.as_str()).collect::<Vec<_>>();
+        let id = match rest.as_slice() {
+            ["properties", name, nested @ ..] => match document_type.properties.iter().position(|p| p.name == *name) {
+                Some(prop_index) => {
+                    let nested_index = match nested {
+                        ["properties", nested_name, ..] => document_type.properties[prop_index].properties.as_ref().and_then(|p| p.iter().position(|p| p.name == *nested_name)),
+                        _ => None,
+                    };
+                    match nested_index {
+                        Some(rec_prop_index) => navigator::rec_property_id(doc_index, prop_index, rec_prop_index),
+                        None => navigator::property_id(doc_index, prop_index),
+                    }
+                }
+                None => navigator::document_type_id(doc_index),
+            },
+            ["indices", index, ..] => match index.parse::<usize>() {
+                Ok(index_index) if index_index < document_type.indices.len() => navigator::index_id(doc_index, index_index),
+                _ => navigator::document_type_id(doc_index),
+            },
+            _ => navigator::document_type_id(doc_index),
+        };
+        Some((doc_index, id))
+    }
+
+    /// Focuses the form element of a clicked JSON node
+    pub fn select_json_node(&mut self, path: Vec<String>) {
+        if let Some((doc_index, id)) = self.form_element(&path) {
+            self.jump_to(doc_index, id);
+        }
+    }
+
+    pub fn toggle_json_fold(&mut self, path: Vec<String>) {
+        if !self.json_folds.remove(&path) {
+            self.json_folds.insert(path);
+        }
+    }
+
+    fn select_callback(ctx: &yew::Context<Self>, path: Vec<String>) -> yew::Callback<MouseEvent> {
+        ctx.link().callback(move |e: MouseEvent| {
+            e.stop_propagation();
+            Msg::SelectJsonNode(path.clone())
+        })
+    }
+
+    /// Adds the lines of a value to `lines`, indented by `depth`
+    fn json_lines(&self, ctx: &yew::Context<Self>, name: Option<&str>, value: &Value, path: Vec<String>, depth: usize, comma: bool, lines: &mut Vec<Html>) {
+        let comma = if comma { "," } else { "" };
+        let is_container = value.is_object() || value.is_array();
+        let len = children(value).len();
+        let folded = self.json_folds.contains(&path);
+        let toggle = if is_container && len > 0 {
+            let fold_path = path.clone();
+            html! {
+                <button class="tree-toggle" title={if folded { "Expand" } else { "Collapse" }} onclick={ctx.link().callback(move |e: MouseEvent| {
+                    e.stop_propagation();
+                    Msg::ToggleJsonFold(fold_path.clone())
+                })}>{if folded { "▸" } else { "▾" }}</button>
+            }
+        } else {
+            html! {}
+        };
+        let (open, close) = brackets(value);
+        let content = if !is_container {
+            html! { <>{scalar(value)}<span class="json-punct">{comma}</span></> }
+        } else if len == 0 {
+            html! { <span class="json-punct">{format!("{}{}{}", open, close, comma)}</span> }
+        } else if folded {
+            html! {
+                <>
+                <span class="json-punct">{open}</span>
+                <span class="json-fold-summary">{format!(" {} {} ", len, if value.is_array() { "items" } else { "keys" })}</span>
+                <span class="json-punct">{format!("{}{}", close, comma)}</span>
+                </>
+            }
+        } else {
+            html! { <span class="json-punct">{open}</span> }
+        };
+        let number = lines.len() + 1;
+        lines.push(html! {
+            <div class="json-line" onclick={Self::select_callback(ctx, path.clone())}>
+                <span class="json-line-number">{number}</span>
+                <span class="json-gutter">{toggle}</span>
+                <span style={format!("padding-left: {}em", depth * 2)}>{for name.map(key)}{content}</span>
+            </div>
+        });
+        if is_container && len > 0 && !folded {
+            let items = children(value);
+            let last = items.len() - 1;
+            for (i, (child_name, segment, child)) in items.into_iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(segment);
+                self.json_lines(ctx, child_name, child, child_path, depth + 1, i < last, lines);
+            }
+            let number = lines.len() + 1;
+            lines.push(html! {
+                <div class="json-line">
+                    <span class="json-line-number">{number}</span>
+                    <span class="json-gutter"></span>
+                    <span style={format!("padding-left: {}em", depth * 2)} class="json-punct">{format!("{}{}", close, comma)}</span>
+                </div>
+            });
+        }
+    }
+
+    /// A value on a single line. Keys are clickable.
+    fn json_inline(&self, ctx: &yew::Context<Self>, value: &Value, path: Vec<String>) -> Html {
+        if !(value.is_object() || value.is_array()) {
+            return scalar(value);
+        }
+        let (open, close) = brackets(value);
+        let items = children(value);
+        let last = items.len().saturating_sub(1);
+        html! {
+            <>
+            <span class="json-punct">{open}</span>
+            {for items.into_iter().enumerate().map(|(i, (name, segment, child))| {
+                let mut child_path = path.clone();
+                child_path.push(segment);
+                html! {
+                    <>
+                    {for name.map(|name| html! {
+                        <span class="json-key" onclick={Self::select_callback(ctx, child_path.clone())}>{Value::String(name.to_string()).to_string()}</span>
+                    })}
+                    {if name.is_some() { html! { <span class="json-punct">{":"}</span> } } else { html! {} }}
+                    {self.json_inline(ctx, child, child_path)}
+                    {if i < last { html! { <span class="json-punct">{","}</span> } } else { html! {} }}
+                    </>
+                }
+            })}
+            <span class="json-punct">{close}</span>
+            </>
+        }
+    }
+
+    /// The contract as highlighted code. Clicking a line selects its form element.
+    pub fn view_json_code(&self, ctx: &yew::Context<Self>) -> Html {
+        let documents = self.documents_json();
+        let code = match self.json_format {
+            JsonFormat::Minified => html! {
+                <div class="json-line json-minified">
+                    <span class="json-line-number">{1}</span>
+                    <span>{self.json_inline(ctx, &documents, vec![])}</span>
+                </div>
+            },
+            JsonFormat::Pretty | JsonFormat::Canonical => {
+                let documents = if self.json_format == JsonFormat::Canonical { sort_keys(&documents) } else { documents };
+                let mut lines = vec![];
+                self.json_lines(ctx, None, &documents, vec![], 0, false, &mut lines);
+                html! { <>{for lines}</> }
+            }
+        };
+        html! {
+            <>
+            <select onchange={ctx.link().callback(|e: Event| Msg::UpdateJsonFormat(JsonFormat::from_label(e.target_dyn_into::<HtmlSelectElement>().unwrap().value().as_str())))}>
+                {for JsonFormat::ALL.iter().map(|format| html! {
+                    <option value={format.label()} selected={*format == self.json_format}>{format.label()}</option>
+                })}
+            </select>
+            <pre class="json-code">{code}</pre>
+            </>
+        }
+    }
+}
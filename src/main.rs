@@ -2,13 +2,54 @@
 
 use std::{collections::{HashMap, HashSet}, sync::Arc};
 use serde::{Serialize, Deserialize};
-use yew::{html, Component, Html, Event, InputEvent, FocusEvent, TargetCast};
+use yew::{html, Component, Html, Event, InputEvent, FocusEvent, KeyboardEvent, DragEvent, TargetCast};
 use serde_json::{json, Map, Value};
 use web_sys::HtmlSelectElement;
-use dpp::{self, consensus::ConsensusError, prelude::Identifier, Convertible};
+use dpp::{self, consensus::ConsensusError, data_contract::DataContractFactory, prelude::Identifier, Convertible};
+
+mod bulk;
+mod diagram;
+mod diff;
+mod docs;
+mod documents;
+mod duplicate;
+mod export;
+mod files;
+mod history;
+mod index_suggestions;
+mod js_sdk;
+mod json_editor;
+mod json_schema;
+mod json_view;
+mod navigator;
+mod rust_structs;
+mod query;
+mod reorder;
+mod samples;
+mod share;
+mod templates;
+mod transition;
+mod typescript;
+mod workspace;
+mod yaml;
+
+use export::ExportFormat;
+use history::History;
+use json_view::JsonFormat;
+use reorder::ListKind;
+use samples::SampleMode;
+use transition::{BaseContract, TransitionEncoding};
+
+/// Content media type that marks a byte array as an identifier
+const IDENTIFIER_MEDIA_TYPE: &str = "application/x.dash.dpp.identifier";
+
+/// Whether a property schema is an identifier: a byte array with the identifier content media type
+fn is_identifier(schema: &Value) -> bool {
+    schema.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) && schema.get("contentMediaType").and_then(|m| m.as_str()) == Some(IDENTIFIER_MEDIA_TYPE)
+}
 
 /// Document type struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[allow(non_snake_case)]
 struct DocumentType {
     name: String,
@@ -33,7 +74,7 @@ impl Default for DocumentType {
 }
 
 /// Property struct with optional fields for validation parameters specific to each data type
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct Property {
     name: String,
     data_type: DataType,
@@ -49,6 +90,7 @@ struct Property {
     byte_array: Option<bool>,  // For Array data type
     min_items: Option<u32>,    // For Array data type
     max_items: Option<u32>,    // For Array data type
+    content_media_type: Option<String>, // For Array data type
     properties: Option<Box<Vec<Property>>>, // For Object data type
     min_properties: Option<u32>, // For Object data type
     max_properties: Option<u32>, // For Object data type
@@ -57,7 +99,7 @@ struct Property {
 }
 
 /// Index struct
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct Index {
     name: String,
     properties: Vec<IndexProperties>,
@@ -65,7 +107,7 @@ struct Index {
 }
 
 /// Index properties struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct IndexProperties(String, String);
 
 impl Default for IndexProperties {
@@ -100,6 +142,125 @@ struct Model {
     imported_json: String,
     /// DPP validation error messages
     error_messages: Vec<String>,
+    /// Base58 ID of the identity that owns the contract and signs the transition
+    transition_identity_id: String,
+    /// ID of the identity key used to sign the transition
+    transition_key_id: u32,
+    /// Private key for the signing identity key, as WIF or hex
+    transition_private_key: String,
+    /// Encoding used to display the serialized transition
+    transition_encoding: TransitionEncoding,
+    /// The last signed and serialized state transition
+    transition_bytes: Vec<u8>,
+    /// Errors from building or signing the state transition
+    transition_messages: Vec<String>,
+    /// Whether the signature of the last transition was verified locally
+    transition_verified: bool,
+    /// A string containing a deployed data contract to update
+    base_contract_json: String,
+    /// The deployed data contract being updated
+    base_contract: Option<BaseContract>,
+    /// Edits that break the data contract update rules
+    update_violations: Vec<String>,
+    /// The generated schemas update_violations were computed for. The check result is shown while the form still generates the same schemas.
+    update_checked_json: Option<Vec<String>>,
+    /// A string containing a contract to compare with the current one
+    compare_json: String,
+    /// Changes from the compared contract to the current one
+    compare_changes: Vec<diff::SchemaChange>,
+    /// RFC 6902 JSON Patch from the compared contract to the current one
+    compare_patch: String,
+    /// Errors from parsing the compared contract
+    compare_messages: Vec<String>,
+    /// Name of the document type sample documents are validated against
+    sample_document_type: String,
+    /// A string containing one or more sample documents
+    sample_documents_input: String,
+    /// Validation results for each sample document
+    sample_results: Vec<documents::DocumentResult>,
+    /// Which kind of example documents to generate
+    sample_mode: SampleMode,
+    /// Generated example documents for each document type
+    generated_samples: Vec<(String, Vec<samples::Sample>)>,
+    /// Format the contract is exported to
+    export_format: ExportFormat,
+    /// The contract in the export format
+    export_output: String,
+    /// A JSON Schema file to import as document types
+    json_schema_input: String,
+    /// What the JSON Schema import changed or dropped
+    json_schema_messages: Vec<String>,
+    /// Document types written as YAML to import
+    yaml_input: String,
+    /// Comments the YAML import couldn't attach, or why it failed
+    yaml_messages: Vec<String>,
+    /// Link that restores the current editor state
+    share_url: String,
+    /// Why sharing or restoring a shared state failed
+    share_messages: Vec<String>,
+    /// Projects saved in browser storage
+    projects: Vec<workspace::Project>,
+    /// ID of the open project
+    current_project: u32,
+    /// Name input for renaming the open project
+    project_name: String,
+    /// Why the workspace couldn't be saved
+    workspace_messages: Vec<String>,
+    /// What was last saved to browser storage
+    saved_workspace: Option<workspace::SavedWorkspace>,
+    /// Undo and redo states of the document types
+    history: History,
+    /// Uploaded files being read, by file name
+    file_readers: HashMap<String, gloo_file::callbacks::FileReader>,
+    /// Why an upload or download failed, and notes from importing a file
+    file_messages: Vec<String>,
+    /// Object URL of the last download, kept alive until the next one
+    download_url: Option<gloo_file::ObjectUrl>,
+    /// Templates the user saved to the gallery
+    user_templates: Vec<templates::UserTemplate>,
+    /// Name input for saving the current contract as a template
+    template_name: String,
+    /// Why saving a template failed
+    template_messages: Vec<String>,
+    /// The list and position of the item being dragged
+    dragging: Option<(ListKind, usize)>,
+    /// Document type whose bulk property entry is open
+    bulk_document_type: Option<usize>,
+    /// Pasted CSV or TSV property rows
+    bulk_input: String,
+    /// Document types collapsed in the navigator and the editor
+    collapsed: HashSet<usize>,
+    /// Filter for the navigator tree
+    navigator_search: String,
+    /// Element to scroll to once the editor has rendered
+    pending_jump: Option<String>,
+    /// Text of the JSON editor
+    json_editor: String,
+    /// The JSON last generated from the form, to tell form changes from other updates
+    json_editor_synced: String,
+    /// Syntax errors in the JSON editor, by line and column, or what the form can't keep from it
+    json_editor_errors: Vec<String>,
+    /// What the form couldn't keep from the last JSON edit, reported again when the form rewrites the JSON
+    json_editor_dropped: Vec<String>,
+    /// The generated schemas error_messages were computed for. The results are shown while the form
+    /// still generates the same schemas.
+    validated_json: Option<Vec<String>>,
+    /// Layout of the highlighted JSON view
+    json_format: JsonFormat,
+    /// Paths of the objects and arrays folded in the JSON view
+    json_folds: HashSet<Vec<String>>,
+    /// Identifier properties marked as references to other document types
+    references: Vec<diagram::Reference>,
+    /// Name of the document type queries are planned for
+    query_document_type: String,
+    /// A query to check against the indices
+    query_input: String,
+    /// Which index serves the query, or why none does
+    query_plan: Option<query::QueryPlan>,
+    /// The queries an app runs, to suggest indices for
+    index_queries_input: String,
+    /// Suggested indices and notes on the declared ones
+    index_report: Option<index_suggestions::IndexReport>,
 }
 
 /// Messages from input fields which call the functions to update Model
@@ -113,6 +274,12 @@ enum Msg {
     AddIndex(usize),
     RemoveIndex(usize, usize),
     AddIndexProperty(usize, usize),
+    DuplicateDocumentType(usize),
+    DuplicateProperty(usize, usize),
+    DuplicateIndex(usize, usize),
+    StartDrag(ListKind, usize),
+    DropItem(ListKind, usize),
+    MoveItem(ListKind, usize, usize),
     UpdateName(usize, String),
     UpdateComment(usize, String),
     UpdatePropertyName(usize, usize, String),
@@ -135,12 +302,14 @@ enum Msg {
     UpdateArrayPropertyByteArray(usize, usize, bool),
     UpdateArrayPropertyMinItems(usize, usize, u32),
     UpdateArrayPropertyMaxItems(usize, usize, u32),
+    UpdateArrayPropertyContentMediaType(usize, usize, String),
     UpdateObjectPropertyMinProperties(usize, usize, u32),
     UpdateObjectPropertyMaxProperties(usize, usize, u32),
 
     // Recursive properties
     AddRecProperty(usize, usize),
     RemoveRecProperty(usize, usize, usize),
+    DuplicateRecProperty(usize, usize, usize),
     UpdateRecPropertyType(usize, usize, usize, String),
     UpdateRecPropertyName(usize, usize, usize, String),
     UpdateRecPropertyRequired(usize, usize, usize, bool),
@@ -155,13 +324,104 @@ enum Msg {
     UpdateArrayRecPropertyByteArray(usize, usize, usize, bool),
     UpdateArrayRecPropertyMinItems(usize, usize, usize, u32),
     UpdateArrayRecPropertyMaxItems(usize, usize, usize, u32),
+    UpdateArrayRecPropertyContentMediaType(usize, usize, usize, String),
     UpdateObjectRecPropertyMaxProperties(usize, usize, usize, u32),
     UpdateObjectRecPropertyMinProperties(usize, usize, usize, u32),
 
     // Import
-    Import,
-    UpdateImportedJson(String),
-    Clear,
+    UpdateJsonEditor(String),
+
+    // State transition
+    UpdateTransitionIdentityId(String),
+    UpdateTransitionKeyId(u32),
+    UpdateTransitionPrivateKey(String),
+    UpdateTransitionEncoding(TransitionEncoding),
+    BuildCreateTransition,
+    UpdateBaseContractJson(String),
+    LoadBaseContract,
+    CheckUpdate,
+    BuildUpdateTransition,
+
+    // Compare
+    UpdateCompareJson(String),
+    Compare,
+
+    // Sample documents
+    UpdateSampleDocumentType(String),
+    UpdateSampleDocuments(String),
+    ValidateSampleDocuments,
+    UpdateSampleMode(SampleMode),
+    GenerateSampleDocuments,
+    UseGeneratedSamples,
+
+    // Export
+    UpdateExportFormat(ExportFormat),
+    Export,
+
+    // JSON Schema
+    UpdateJsonSchemaInput(String),
+    ImportJsonSchema,
+
+    // YAML
+    UpdateYamlInput(String),
+    ImportYaml,
+
+    // Share
+    Share,
+
+    // Projects
+    OpenProject(u32),
+    UpdateProjectName(String),
+    NewProject,
+    RenameProject,
+    DuplicateProject,
+    DeleteProject,
+
+    // History
+    Undo,
+    Redo,
+    JumpToHistory(usize),
+
+    // Files
+    UploadFiles(Vec<web_sys::File>),
+    FileLoaded(String, Result<Vec<u8>, String>),
+    Download(files::Download),
+
+    // Templates
+    UseTemplate(usize),
+    UseUserTemplate(usize),
+    UpdateTemplateName(String),
+    SaveTemplate,
+    DeleteUserTemplate(usize),
+
+    // Bulk properties
+    OpenBulkEntry(usize),
+    CloseBulkEntry,
+    UpdateBulkInput(String),
+    ApplyBulkEntry,
+
+    // Navigator
+    UpdateNavigatorSearch(String),
+    ToggleCollapsed(usize),
+    JumpTo(usize, String),
+
+    // JSON view
+    UpdateJsonFormat(JsonFormat),
+    ToggleJsonFold(Vec<String>),
+    SelectJsonNode(Vec<String>),
+
+    // Diagram
+    UpdateReference(String, String, String),
+
+    // Query planner
+    UpdateQueryDocumentType(String),
+    UpdateQueryInput(String),
+    PlanQuery,
+
+    // Index suggestions
+    UpdateIndexQueries(String),
+    SuggestIndices,
+    AddSuggestedIndices,
 }
 
 /// Sets the validation parameters to default. Used to reset the fields when a 
@@ -187,6 +447,7 @@ fn default_additional_properties(data_type: &str) -> Property {
             byte_array: None,
             min_items: None,
             max_items: None,
+            content_media_type: None,
             ..Default::default()
         },
         "Object" => Property {
@@ -207,6 +468,62 @@ fn default_additional_properties(data_type: &str) -> Property {
     }
 }
 
+/// Reads a property schema into the form's fields, leaving out inner properties. `required` is
+/// the required array of the object the property belongs to.
+fn parse_property(name: &str, display_name: &str, schema: &Value, required: Option<&Value>, errors: &mut Vec<String>) -> Property {
+    // Create a new default Property and set its name
+    let mut property = Property::default();
+    property.name = name.to_string();
+    property.required = required.and_then(|r| r.as_array()).map_or(false, |r| r.iter().any(|v| v.as_str() == Some(name)));
+
+    // Check if property value is an object
+    let prop_obj = match schema.as_object() {
+        Some(prop_obj) => prop_obj,
+        None => {
+            errors.push(format!("Property \"{}\" must be an object", display_name));
+            return property;
+        }
+    };
+    // Set the Property.data_type to the value of "type"
+    property.data_type = match prop_obj.get("type") {
+        Some(Value::String(data_type)) => match data_type.as_str() {
+            "string" => DataType::String,
+            "integer" => DataType::Integer,
+            "array" => DataType::Array,
+            "object" => DataType::Object,
+            "number" => DataType::Number,
+            "boolean" => DataType::Boolean,
+            other => {
+                errors.push(format!("Property \"{}\" has unknown type \"{}\". Use string, integer, array, object, number or boolean", display_name, other));
+                DataType::default()
+            }
+        },
+        Some(_) => {
+            errors.push(format!("The type of property \"{}\" must be a string", display_name));
+            DataType::default()
+        }
+        None => {
+            errors.push(format!("Property \"{}\" has no type", display_name));
+            DataType::default()
+        }
+    };
+    property.byte_array = prop_obj.get("byteArray").and_then(|v| v.as_bool());
+    property.description = prop_obj.get("description").and_then(|v| v.as_str()).map(|s| s.to_string());
+    property.comment = prop_obj.get("$comment").and_then(|v| v.as_str()).map(|s| s.to_string());
+    property.min_length = prop_obj.get("minLength").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property.max_length = prop_obj.get("maxLength").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property.pattern = prop_obj.get("pattern").and_then(|v| v.as_str()).map(|s| s.to_string());
+    property.format = prop_obj.get("format").and_then(|v| v.as_str()).map(|s| s.to_string());
+    property.minimum = prop_obj.get("minimum").and_then(|v| v.as_i64()).map(|num| num as i32);
+    property.maximum = prop_obj.get("maximum").and_then(|v| v.as_i64()).map(|num| num as i32);
+    property.min_items = prop_obj.get("minItems").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property.max_items = prop_obj.get("maxItems").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property.content_media_type = prop_obj.get("contentMediaType").and_then(|v| v.as_str()).map(|s| s.to_string());
+    property.min_properties = prop_obj.get("minProperties").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property.max_properties = prop_obj.get("maxProperties").and_then(|v| v.as_u64()).map(|num| num as u32);
+    property
+}
+
 // Contains functions that generate the webpage and json object
 impl Model {
 
@@ -219,11 +536,30 @@ impl Model {
     }
 
     fn view_document_type(&self, index: usize, ctx: &yew::Context<Self>) -> Html {
+        let collapsed = self.collapsed.contains(&index);
+        let heading = html! {
+            <h2>
+                <button class="tree-toggle" title={if collapsed { "Expand" } else { "Collapse" }} onclick={ctx.link().callback(move |_| Msg::ToggleCollapsed(index))}>{if collapsed { "▸" } else { "▾" }}</button>
+                {format!("Document type {} ", index+1)}{self.view_reorder_handle(ctx, ListKind::DocumentTypes, index)}
+            </h2>
+        };
+        if collapsed {
+            return html! {
+                <>
+                <div class="input-container" id={navigator::document_type_id(index)}>
+                    {heading}
+                    <b>{self.document_types[index].name.clone()}</b>
+                    {format!(" — {} properties, {} indices", self.document_types[index].properties.len(), self.document_types[index].indices.len())}
+                </div>
+                <br/>
+                </>
+            };
+        }
         html! {
             <>
-            <div class="input-container">
+            <div class="input-container" id={navigator::document_type_id(index)}>
                 <div>
-                    <h2>{format!("Document type {}", index+1)}</h2>
+                    {heading}
                     <h3>{"Name"}</h3>
                     <input type="text" placeholder="Name" value={self.document_types[index].name.clone()} onblur={ctx.link().callback(move |e: FocusEvent| Msg::UpdateName(index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                 </div>
@@ -237,6 +573,7 @@ impl Model {
                             </tr>
                         </tbody>
                     </table>
+                    {self.view_bulk_entry(ctx, index)}
                 </div>
                 <div>
                     <h3>{"Indices"}</h3>
@@ -256,6 +593,7 @@ impl Model {
                 <br/>
                 <div>
                 <button class="button" onclick={ctx.link().callback(move |_| Msg::RemoveDocumentType(index))}>{format!("Remove document type {}", index+1)}</button>
+                <button class="button2" onclick={ctx.link().callback(move |_| Msg::DuplicateDocumentType(index))}>{format!("Duplicate document type {}", index+1)}</button>
                 </div>
             </div>
             <br/>
@@ -282,7 +620,7 @@ impl Model {
                     <th>{"Required"}</th>
                 </tr>
                 <tr>
-                    <td><input type="text3" placeholder={format!("Property {} name", prop_index+1)} value={self.document_types[doc_index].properties[prop_index].name.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyName(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
+                    <td><input type="text3" id={navigator::property_id(doc_index, prop_index)} placeholder={format!("Property {} name", prop_index+1)} value={self.document_types[doc_index].properties[prop_index].name.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyName(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
                     <td>
                         <select value={selected_data_type.clone()} onchange={ctx.link().callback(move |e: Event| {
                             let selected_data_type = e.target_dyn_into::<HtmlSelectElement>().unwrap().value();
@@ -296,6 +634,8 @@ impl Model {
                     </td>
                     <td><input type="checkbox" checked={self.document_types[doc_index].properties[prop_index].required} onchange={ctx.link().callback(move |e: Event| Msg::UpdatePropertyRequired(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} /></td>
                     <td><button class="button" onclick={ctx.link().callback(move |_| Msg::RemoveProperty(doc_index, prop_index))}>{"Remove"}</button></td>
+                    <td><button class="button2" onclick={ctx.link().callback(move |_| Msg::DuplicateProperty(doc_index, prop_index))}>{"Duplicate"}</button></td>
+                    <td>{self.view_reorder_handle(ctx, ListKind::Properties(doc_index), prop_index)}</td>
                 </tr>
                 <p><b>{if selected_data_type != String::from("Object") { "Optional property parameters:" } else {""}}</b></p>
                 <tr>
@@ -365,6 +705,10 @@ impl Model {
                     <td><label>{"Max items: "}</label></td>
                     <td><input type="number" value={property.max_items.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayPropertyMaxItems(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value_as_number() as u32))} /></td>
                 </tr>
+                <tr>
+                    <td><label>{"Content media type: "}</label></td>
+                    <td><input type="text3" placeholder={IDENTIFIER_MEDIA_TYPE} value={property.content_media_type.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayPropertyContentMediaType(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
+                </tr>
                 </>
             },
             "Object" => html! {
@@ -427,7 +771,7 @@ impl Model {
                 </tr>
                 <tr>
                     <td>
-                        <input type="text3" placeholder={format!("Inner property {} name", recursive_prop_index+1)} value={match &self.document_types[doc_index].properties[prop_index].properties {
+                        <input type="text3" id={navigator::rec_property_id(doc_index, prop_index, recursive_prop_index)} placeholder={format!("Inner property {} name", recursive_prop_index+1)} value={match &self.document_types[doc_index].properties[prop_index].properties {
                             Some(properties) => properties.get(recursive_prop_index).map(|property| property.name.clone()).unwrap_or_default(),
                             None => String::new(),
                         }} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateRecPropertyName(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
@@ -456,6 +800,10 @@ impl Model {
                     <td>
                         <button class="button" onclick={ctx.link().callback(move |_| Msg::RemoveRecProperty(doc_index, prop_index, recursive_prop_index))}>{"Remove"}</button>
                     </td>
+                    <td>
+                        <button class="button2" onclick={ctx.link().callback(move |_| Msg::DuplicateRecProperty(doc_index, prop_index, recursive_prop_index))}>{"Duplicate"}</button>
+                    </td>
+                    <td>{self.view_reorder_handle(ctx, ListKind::RecProperties(doc_index, prop_index), recursive_prop_index)}</td>
                 </tr>
                 <p><b>{"Optional property parameters:"}</b></p>
                 <tr>
@@ -536,6 +884,7 @@ impl Model {
                 let byte_array = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.byte_array);
                 let max_items = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.max_items);
                 let min_items = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.min_items);
+                let content_media_type = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.content_media_type.clone());
             
                 html! {
                     <>
@@ -565,6 +914,10 @@ impl Model {
                             Msg::UpdateArrayRecPropertyMaxItems(doc_index, prop_index, recursive_prop_index, value.unwrap_or(0))
                         })} value={max_items.map(|n| n.to_string()).unwrap_or_default().to_owned()} /></td>
                     </tr>
+                    <tr>
+                        <td><label>{"Content media type: "}</label></td>
+                        <td><input type="text3" placeholder={IDENTIFIER_MEDIA_TYPE} value={content_media_type.unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayRecPropertyContentMediaType(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
+                    </tr>
                     </>
                 }
             },            
@@ -621,9 +974,11 @@ impl Model {
                 <th>{""}</th>
             </tr>
             <tr>
-                <td><input type="text3" placeholder={format!("Index {} name", index_index+1)} value={self.document_types[doc_index].indices[index_index].name.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateIndexName(doc_index, index_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
+                <td><input type="text3" id={navigator::index_id(doc_index, index_index)} placeholder={format!("Index {} name", index_index+1)} value={self.document_types[doc_index].indices[index_index].name.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateIndexName(doc_index, index_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} /></td>
                 <td><input type="checkbox" checked={self.document_types[doc_index].indices[index_index].unique} onchange={ctx.link().callback(move |e: Event| Msg::UpdateIndexUnique(doc_index, index_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} /></td>
                 <td><button class="button" onclick={ctx.link().callback(move |_| Msg::RemoveIndex(doc_index, index_index))}>{"Remove"}</button></td>
+                <td><button class="button2" onclick={ctx.link().callback(move |_| Msg::DuplicateIndex(doc_index, index_index))}>{"Duplicate"}</button></td>
+                <td>{self.view_reorder_handle(ctx, ListKind::Indices(doc_index), index_index)}</td>
             </tr>
             <tr>
                 <td colspan="3">
@@ -663,6 +1018,7 @@ impl Model {
                         <option value={String::from(*option)} selected={&String::from(*option)==current_sort}>{String::from(*option)}</option>
                     })}
                 </select></td>
+                <td>{self.view_reorder_handle(ctx, ListKind::IndexProperties(doc_index, index_index), prop_index)}</td>
             </tr>
         )
     }
@@ -714,6 +1070,9 @@ impl Model {
                 if prop.max_items.as_ref().map(|c| *c).unwrap_or(0) > 0 {
                     prop_obj.insert("maxItems".to_owned(), json!(prop.max_items));
                 }
+                if prop.content_media_type.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
+                    prop_obj.insert("contentMediaType".to_owned(), json!(prop.content_media_type));
+                }
                 if prop.data_type == DataType::Object {
                     let rec_props_map = Self::generate_nested_properties(prop);
                     prop_obj.insert("properties".to_owned(), json!(rec_props_map));
@@ -834,6 +1193,9 @@ impl Model {
                 if rec_prop.max_items.as_ref().map(|c| *c).unwrap_or(0) > 0 {
                     rec_prop_obj.insert("maxItems".to_owned(), json!(rec_prop.max_items));
                 }
+                if rec_prop.content_media_type.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
+                    rec_prop_obj.insert("contentMediaType".to_owned(), json!(rec_prop.content_media_type));
+                }
                 if rec_prop.data_type == DataType::Object {
                     rec_prop_obj.insert("properties".to_owned(), json!({}));
                 }
@@ -865,230 +1227,121 @@ impl Model {
         rec_props_map
     }
 
-    fn parse_imported_json(&mut self) {
+    /// Replaces the document types with the ones in imported_json. If a schema can't be read into
+    /// the form, the document types are left as they were and the problems are returned. Otherwise
+    /// returns notes on what the form couldn't keep.
+    fn parse_imported_json(&mut self) -> Result<Vec<String>, Vec<String>> {
 
-        // Parse the string into a HashMap
-        let parsed_json: HashMap<String, Value> = serde_json::from_str(&self.imported_json).unwrap_or_default();
+        // Parse the string into a Map, keeping the order of the document types
+        let parsed_json: Map<String, Value> = serde_json::from_str(&self.imported_json).map_err(|e| vec![format!("Invalid JSON: {}", e)])?;
 
-        // Convert the HashMap into a Vec of Strings for json_object
-        self.json_object = parsed_json.iter().map(|(k, v)| {
-            format!("\"{}\":{}", k, v.to_string())
-        }).collect();
-
-        // Empty self.document_types
-        self.document_types = Vec::new();
+        let mut errors = Vec::new();
+        let mut document_types = Vec::new();
 
         // Iterate over each key-value pair in the parsed JSON and push to document_types
-        for (doc_type_name, doc_type_value) in parsed_json {
+        for (doc_type_name, doc_type_value) in &parsed_json {
             // Create a new default DocumentType and set its name
             let mut document_type = DocumentType::default();
-            document_type.name = doc_type_name;
+            document_type.name = doc_type_name.clone();
 
             // Check if value is an object
-            if let Some(doc_type_obj) = doc_type_value.as_object() {
-                // Iterate over properties
-                if let Some(properties) = doc_type_obj.get("properties") {
-                    if let Some(properties_obj) = properties.as_object() {
-                        for (prop_name, prop_value) in properties_obj {
-                            // Create a new default Property and set its name
-                            let mut property = Property::default();
-                            property.name = prop_name.to_string();
-
-                            if let Some(required) = doc_type_obj.get("required") {
-                                if let Some(required_array) = required.as_array() {
-                                    if required_array.iter().any(|v| *v == Value::String(prop_name.clone())) {
-                                        property.required = true;
-                                    }
-                                }
-                            }
+            let doc_type_obj = match doc_type_value.as_object() {
+                Some(doc_type_obj) => doc_type_obj,
+                None => {
+                    errors.push(format!("Document type \"{}\" must be an object", doc_type_name));
+                    continue;
+                }
+            };
 
-                            // Check if property value is an object
-                            if let Some(prop_obj) = prop_value.as_object() {
-                                // Set the Property.data_type to the value of "type"
-                                if let Some(data_type) = prop_obj.get("type") {
-                                    property.data_type = match data_type.as_str().unwrap() {
-                                        "string" => DataType::String,
-                                        "integer" => DataType::Integer,
-                                        "array" => DataType::Array,
-                                        "object" => DataType::Object,
-                                        "number" => DataType::Number,
-                                        "boolean" => DataType::Boolean,
-                                        _ => panic!("Unexpected type value"),
-                                    };
-                                }
-                                if let Some(byte_array) = prop_obj.get("byteArray") {
-                                    property.byte_array = byte_array.as_bool();
-                                }
-                                if let Some(description) = prop_obj.get("description") {
-                                    property.description = description.as_str().map(|s| s.to_string());
-                                }
-                                if let Some(comment) = prop_obj.get("$comment") {
-                                    property.comment = comment.as_str().map(|s| s.to_string());
-                                }
-                                if let Some(min_length) = prop_obj.get("minLength") {
-                                    property.min_length = min_length.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(max_length) = prop_obj.get("maxLength") {
-                                    property.max_length = max_length.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(pattern) = prop_obj.get("pattern") {
-                                    property.pattern = pattern.as_str().map(|s| s.to_string());
-                                }
-                                if let Some(format) = prop_obj.get("format") {
-                                    property.format = format.as_str().map(|s| s.to_string());
-                                }
-                                if let Some(minimum) = prop_obj.get("minimum") {
-                                    property.minimum = minimum.as_i64().map(|num| num as i32);
-                                }
-                                if let Some(maximum) = prop_obj.get("maximum") {
-                                    property.maximum = maximum.as_i64().map(|num| num as i32);
-                                }
-                                if let Some(min_items) = prop_obj.get("minItems") {
-                                    property.min_items = min_items.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(max_items) = prop_obj.get("maxItems") {
-                                    property.max_items = max_items.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(min_properties) = prop_obj.get("minProperties") {
-                                    property.min_properties = min_properties.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(max_properties) = prop_obj.get("maxProperties") {
-                                    property.max_properties = max_properties.as_u64().map(|num| num as u32);
-                                }
-                                if let Some(nested_props) = prop_obj.get("properties") {
-                                    if let Some(nested_props_map) = nested_props.as_object() {
-                                        let mut nested_props_vec = Vec::new();
-                                        for (nested_prop_name, nested_prop_value) in nested_props_map {
-                                            let mut nested_property = Property::default();
-                                            nested_property.name = nested_prop_name.clone();
-                                            if let Some(rec_required) = prop_obj.get("required") {
-                                                if let Some(rec_required_array) = rec_required.as_array() {
-                                                    if rec_required_array.iter().any(|v| *v == Value::String(nested_prop_name.clone())) {
-                                                        nested_property.required = true;
-                                                    }
-                                                }
-                                            }
-                                            if let Some(nested_prop_obj) = nested_prop_value.as_object() {
-                                                if let Some(data_type) = nested_prop_obj.get("type") {
-                                                    nested_property.data_type = match data_type.as_str().unwrap() {
-                                                        "string" => DataType::String,
-                                                        "integer" => DataType::Integer,
-                                                        "array" => DataType::Array,
-                                                        "object" => DataType::Object,
-                                                        "number" => DataType::Number,
-                                                        "boolean" => DataType::Boolean,
-                                                        _ => panic!("Unexpected type value"),
-                                                    };
-                                                }
-                                                if let Some(byte_array) = nested_prop_obj.get("byteArray") {
-                                                    nested_property.byte_array = byte_array.as_bool();
-                                                }
-                                                if let Some(description) = nested_prop_obj.get("description") {
-                                                    nested_property.description = description.as_str().map(|s| s.to_string());
-                                                }
-                                                if let Some(comment) = nested_prop_obj.get("$comment") {
-                                                    nested_property.comment = comment.as_str().map(|s| s.to_string());
-                                                }
-                                                if let Some(min_length) = nested_prop_obj.get("minLength") {
-                                                    nested_property.min_length = min_length.as_u64().map(|num| num as u32);
-                                                }
-                                                if let Some(max_length) = nested_prop_obj.get("maxLength") {
-                                                    nested_property.max_length = max_length.as_u64().map(|num| num as u32);
-                                                }
-                                                if let Some(pattern) = nested_prop_obj.get("pattern") {
-                                                    nested_property.pattern = pattern.as_str().map(|s| s.to_string());
-                                                }
-                                                if let Some(format) = nested_prop_obj.get("format") {
-                                                    nested_property.format = format.as_str().map(|s| s.to_string());
-                                                }
-                                                if let Some(minimum) = nested_prop_obj.get("minimum") {
-                                                    nested_property.minimum = minimum.as_i64().map(|num| num as i32);
-                                                }
-                                                if let Some(maximum) = nested_prop_obj.get("maximum") {
-                                                    nested_property.maximum = maximum.as_i64().map(|num| num as i32);
-                                                }
-                                                if let Some(min_items) = nested_prop_obj.get("minItems") {
-                                                    nested_property.min_items = min_items.as_u64().map(|num| num as u32);
-                                                }
-                                                if let Some(max_items) = nested_prop_obj.get("maxItems") {
-                                                    nested_property.max_items = max_items.as_u64().map(|num| num as u32);
-                                                }
-                                                if let Some(min_properties) = nested_prop_obj.get("minProperties") {
-                                                    nested_property.min_properties = min_properties.as_u64().map(|num| num as u32);
-                                                }
-                                                if let Some(max_properties) = nested_prop_obj.get("maxProperties") {
-                                                    nested_property.max_properties = max_properties.as_u64().map(|num| num as u32);
-                                                }
-                                                nested_props_vec.push(nested_property);
-                                            }
-                                        }
-                                        property.properties = Some(Box::new(nested_props_vec));
-                                    }
-                                }
-                            }
-                            // Add the property to the DocumentType
-                            document_type.properties.push(property);
-                        }
+            // Iterate over properties
+            if let Some(properties_obj) = doc_type_obj.get("properties").and_then(|p| p.as_object()) {
+                for (prop_name, prop_value) in properties_obj {
+                    let display_name = format!("{}.{}", doc_type_name, prop_name);
+                    let mut property = parse_property(prop_name, &display_name, prop_value, doc_type_obj.get("required"), &mut errors);
+
+                    // Inner properties, one level deep as in the form
+                    if let Some(nested_props_map) = prop_value.get("properties").and_then(|p| p.as_object()) {
+                        let nested_props_vec = nested_props_map
+                            .iter()
+                            .map(|(nested_prop_name, nested_prop_value)| {
+                                let nested_display_name = format!("{}.{}", display_name, nested_prop_name);
+                                parse_property(nested_prop_name, &nested_display_name, nested_prop_value, prop_value.get("required"), &mut errors)
+                            })
+                            .collect();
+                        property.properties = Some(Box::new(nested_props_vec));
                     }
+
+                    // Add the property to the DocumentType
+                    document_type.properties.push(property);
                 }
+            }
+
+            // Iterate over indices
+            for index_value in doc_type_obj.get("indices").and_then(|i| i.as_array()).into_iter().flatten() {
+                // Check if index value is an object
+                if let Some(index_obj) = index_value.as_object() {
+                    // Create a new default Index
+                    let mut index = Index::default();
+
+                    // Set index name
+                    match index_obj.get("name") {
+                        Some(Value::String(name)) => index.name = name.clone(),
+                        Some(_) => errors.push(format!("An index name of \"{}\" must be a string", doc_type_name)),
+                        None => {}
+                    }
+
+                    // Set unique
+                    match index_obj.get("unique") {
+                        Some(Value::Bool(unique)) => index.unique = *unique,
+                        Some(_) => errors.push(format!("unique of index \"{}\" in \"{}\" must be true or false", index.name, doc_type_name)),
+                        None => {}
+                    }
 
-                // Iterate over indices
-                if let Some(indices) = doc_type_obj.get("indices") {
-                    if let Some(indices_array) = indices.as_array() {
-                        for index_value in indices_array {
-                            // Check if index value is an object
-                            if let Some(index_obj) = index_value.as_object() {
-                                // Create a new default Index
-                                let mut index = Index::default();
-
-                                // Set index name
-                                if let Some(name) = index_obj.get("name") {
-                                    index.name = name.as_str().unwrap().to_string();
-                                }
-
-                                // Set unique
-                                if let Some(unique) = index_obj.get("unique") {
-                                    index.unique = unique.as_bool().unwrap();
-                                }
-
-                                // Iterate over index properties
-                                if let Some(properties) = index_obj.get("properties") {
-                                    if let Some(properties_array) = properties.as_array() {
-                                        for prop_value in properties_array {
-                                            // Check if property value is an object
-                                            if let Some(prop_obj) = prop_value.as_object() {
-                                                // Create a new default IndexProperties
-                                                let mut index_properties = IndexProperties::default();
-
-                                                // Set index properties name and order
-                                                for (name, order) in prop_obj {
-                                                    index_properties.0 = name.to_string();
-                                                    index_properties.1 = order.as_str().unwrap().to_string();
-                                                }
-
-                                                // Add index properties to the Index
-                                                index.properties.push(index_properties);
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Add the index to the DocumentType
-                                document_type.indices.push(index);
+                    // Iterate over index properties, each an object of one name and its order
+                    for prop_obj in index_obj.get("properties").and_then(|p| p.as_array()).into_iter().flatten().filter_map(|p| p.as_object()) {
+                        let mut index_properties = IndexProperties::default();
+                        for (name, order) in prop_obj {
+                            index_properties.0 = name.to_string();
+                            match order.as_str() {
+                                Some(order @ ("asc" | "desc")) => index_properties.1 = order.to_string(),
+                                _ => errors.push(format!("The order of \"{}\" in index \"{}\" of \"{}\" must be \"asc\" or \"desc\"", name, index.name, doc_type_name)),
                             }
                         }
+                        index.properties.push(index_properties);
                     }
-        
-                    // Process comment
-                    if let Some(comment) = doc_type_obj.get("$comment") {
-                        document_type.comment = comment.as_str().unwrap().to_string();
-                    }
+
+                    // Add the index to the DocumentType
+                    document_type.indices.push(index);
                 }
-        
-                // Push to document_types
-                self.document_types.push(document_type);
             }
+
+            // Process comment
+            match doc_type_obj.get("$comment") {
+                Some(Value::String(comment)) => document_type.comment = comment.clone(),
+                Some(_) => errors.push(format!("$comment of \"{}\" must be a string", doc_type_name)),
+                None => {}
+            }
+
+            // Push to document_types
+            document_types.push(document_type);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
         }
+
+        // Convert the Map into a Vec of Strings for json_object
+        self.json_object = parsed_json.iter().map(|(k, v)| {
+            format!("\"{}\":{}", k, v.to_string())
+        }).collect();
+        let old_names = self.document_types.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+        self.document_types = document_types;
+        self.remap_document_types_by_name(&old_names);
+
+        // Generate the JSON back from the form to find what it didn't keep
+        let generated: Value = serde_json::from_str(&format!("{{{}}}", self.generate_json_object().join(","))).unwrap_or_default();
+        let mut dropped = Vec::new();
+        json_editor::dropped_keywords("", &Value::Object(parsed_json), &generated, &mut dropped);
+        Ok(dropped)
     }
 
     fn validate(&mut self) -> Vec<String> {
@@ -1096,9 +1349,7 @@ impl Model {
         let new_s = format!("{{{}}}", s);
         let json_obj: serde_json::Value = serde_json::from_str(&new_s).unwrap();
 
-        let protocol_version_validator = dpp::version::ProtocolVersionValidator::default();
-        let data_contract_validator = dpp::data_contract::validation::data_contract_validator::DataContractValidator::new(Arc::new(protocol_version_validator));
-        let factory = dpp::data_contract::DataContractFactory::new(1, Arc::new(data_contract_validator));
+        let factory = data_contract_factory();
         let owner_id = Identifier::random();
         let contract = factory
             .create(owner_id, json_obj.clone().into(), None, None)
@@ -1108,6 +1359,88 @@ impl Model {
         self.extract_basic_error_messages(&errors)
     }
 
+    /// The document schemas of json_object as a single JSON object
+    fn documents_json(&self) -> Value {
+        let s = &self.json_object.join(",");
+        serde_json::from_str(&format!("{{{}}}", s)).unwrap()
+    }
+
+    fn build_create_transition(&mut self) {
+        self.json_object = self.generate_json_object();
+        let result = transition::build_create_transition(self.documents_json(), &self.transition_identity_id, &self.transition_private_key, self.transition_key_id);
+        self.set_transition_result(result);
+    }
+
+    fn load_base_contract(&mut self) {
+        match transition::parse_base_contract(&self.base_contract_json) {
+            Ok(mut base) => {
+                self.imported_json = Value::Object(base.documents.clone()).to_string();
+                match self.parse_imported_json() {
+                    Ok(dropped) => {
+                        self.json_object = self.generate_json_object();
+                        base.generated = self.documents_json().as_object().cloned().unwrap_or_default();
+                        base.dropped = dropped;
+                        self.base_contract = Some(base);
+                        self.update_violations = vec![];
+                        self.update_checked_json = None;
+                        self.transition_messages = vec![];
+                    }
+                    Err(errors) => {
+                        self.base_contract = None;
+                        self.transition_messages = errors;
+                    }
+                }
+            }
+            Err(message) => {
+                self.base_contract = None;
+                self.transition_messages = vec![message];
+            }
+        }
+    }
+
+    fn check_update(&mut self) {
+        self.json_object = self.generate_json_object();
+        let documents = self.documents_json();
+        match &self.base_contract {
+            Some(base) => {
+                self.update_violations = transition::update_violations(base, &documents);
+                self.update_checked_json = Some(self.json_object.clone());
+                self.transition_messages = vec![];
+            }
+            None => {
+                self.update_violations = vec![];
+                self.update_checked_json = None;
+                self.transition_messages = vec![String::from("Load a base contract first")];
+            }
+        }
+    }
+
+    fn build_update_transition(&mut self) {
+        self.check_update();
+        let result = match &self.base_contract {
+            Some(base) if !base.dropped.is_empty() => Err(String::from("The form couldn't keep everything in the deployed contract, and the update would remove it")),
+            Some(_) if !self.update_violations.is_empty() => Err(String::from("The edits break the data contract update rules")),
+            Some(base) => transition::build_update_transition(base, self.documents_json(), &self.transition_private_key, self.transition_key_id),
+            None => Err(String::from("Load a base contract first")),
+        };
+        self.set_transition_result(result);
+    }
+
+    fn set_transition_result(&mut self, result: Result<transition::SignedTransition, String>) {
+        match result {
+            Ok(signed) => {
+                self.transition_bytes = signed.bytes;
+                self.transition_verified = signed.verified;
+                self.transition_messages = vec![];
+            }
+            Err(message) => {
+                self.transition_bytes = vec![];
+                self.transition_verified = false;
+                self.transition_messages = vec![message];
+            }
+        }
+    }
+
     fn extract_basic_error_messages(&self, errors: &[ConsensusError]) -> Vec<String> {
         let messages: Vec<String> = errors
             .iter()
@@ -1138,15 +1471,91 @@ impl Component for Model {
     fn create(_ctx: &yew::Context<Self>) -> Self {
         let mut default_document_type = DocumentType::default();
         default_document_type.properties.push(Property::default());
-        Self {
+        let mut model = Self {
             document_types: vec![default_document_type],
             json_object: vec![],
             imported_json: String::new(),
             error_messages: vec![],
+            transition_identity_id: String::new(),
+            transition_key_id: 0,
+            transition_private_key: String::new(),
+            transition_encoding: TransitionEncoding::default(),
+            transition_bytes: vec![],
+            transition_messages: vec![],
+            transition_verified: false,
+            base_contract_json: String::new(),
+            base_contract: None,
+            update_violations: vec![],
+            update_checked_json: None,
+            compare_json: String::new(),
+            compare_changes: vec![],
+            compare_patch: String::new(),
+            compare_messages: vec![],
+            sample_document_type: String::new(),
+            sample_documents_input: String::new(),
+            sample_results: vec![],
+            sample_mode: SampleMode::default(),
+            generated_samples: vec![],
+            export_format: ExportFormat::default(),
+            export_output: String::new(),
+            json_schema_input: String::new(),
+            json_schema_messages: vec![],
+            yaml_input: String::new(),
+            yaml_messages: vec![],
+            share_url: String::new(),
+            share_messages: vec![],
+            projects: vec![],
+            current_project: 0,
+            project_name: String::new(),
+            workspace_messages: vec![],
+            saved_workspace: None,
+            history: History::new(&[], &[]),
+            file_readers: HashMap::new(),
+            file_messages: vec![],
+            download_url: None,
+            user_templates: templates::load_user_templates(),
+            template_name: String::new(),
+            template_messages: vec![],
+            dragging: None,
+            bulk_document_type: None,
+            bulk_input: String::new(),
+            collapsed: HashSet::new(),
+            navigator_search: String::new(),
+            pending_jump: None,
+            json_editor: String::new(),
+            json_editor_synced: String::new(),
+            json_editor_errors: vec![],
+            json_editor_dropped: vec![],
+            validated_json: None,
+            json_format: JsonFormat::default(),
+            json_folds: HashSet::new(),
+            references: vec![],
+            query_document_type: String::new(),
+            query_input: String::new(),
+            query_plan: None,
+            index_queries_input: String::new(),
+            index_report: None,
+        };
+        model.open_workspace();
+        // A shared state opens as a new project, and is removed from the URL so a reload doesn't add it again
+        match share::state_from_location() {
+            Some(Ok(state)) => {
+                model.create_project(String::from("Shared draft"), state.document_types);
+                model.references = state.references;
+                share::clear_location_state();
+            }
+            Some(Err(message)) => model.share_messages = vec![message],
+            None => {}
         }
+        model.sync_json_editor();
+        model.history = History::new(&model.document_types, &model.references);
+        model
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        let step = history::Step::of(&msg);
+        let project = self.current_project;
+        let from_json_editor = matches!(msg, Msg::UpdateJsonEditor(_));
         match msg {
             // General
             Msg::AddDocumentType => {
@@ -1166,6 +1575,11 @@ impl Component for Model {
             }
             Msg::RemoveDocumentType(index) => {
                 self.document_types.remove(index);
+                self.remap_document_types(|i| match i {
+                    i if i == index => None,
+                    i if i > index => Some(i - 1),
+                    i => Some(i),
+                });
             }
             Msg::RemoveProperty(doc_index, prop_index) => {
                 let name = self.document_types[doc_index].properties[prop_index].name.clone();
@@ -1181,19 +1595,39 @@ impl Component for Model {
             Msg::AddIndexProperty(doc_index, index_index) => {
                 self.document_types[doc_index].indices[index_index].properties.push(Default::default());
             }
+            Msg::DuplicateDocumentType(index) => {
+                self.duplicate_document_type(index);
+            }
+            Msg::DuplicateProperty(doc_index, prop_index) => {
+                self.duplicate_property(doc_index, prop_index);
+            }
+            Msg::DuplicateIndex(doc_index, index_index) => {
+                self.duplicate_index(doc_index, index_index);
+            }
+            Msg::StartDrag(list, index) => {
+                self.dragging = Some((list, index));
+            }
+            Msg::DropItem(list, index) => {
+                self.drop_item(list, index);
+            }
+            Msg::MoveItem(list, from, to) => {
+                self.move_item(list, from, to);
+            }
             Msg::Submit => {
                 self.json_object = Some(self.generate_json_object()).unwrap();
                 self.error_messages = Some(self.validate()).unwrap();
-                self.imported_json = String::new();
+                self.validated_json = Some(self.json_object.clone());
             }
             Msg::UpdateName(index, name) => {
-                self.document_types[index].name = name;
+                let old = std::mem::replace(&mut self.document_types[index].name, name.clone());
+                self.rename_document_type_references(&old, &name);
             }
             Msg::UpdateComment(index, comment) => {
                 self.document_types[index].comment = comment;
             }
             Msg::UpdatePropertyName(doc_index, prop_index, name) => {
-                self.document_types[doc_index].properties[prop_index].name = name;
+                let old = std::mem::replace(&mut self.document_types[doc_index].properties[prop_index].name, name.clone());
+                self.rename_property_references(&self.document_types[doc_index].name.clone(), &old, &name);
             }
             Msg::UpdateIndexName(doc_index, index_index, name) => {
                 self.document_types[doc_index].indices[index_index].name = name;
@@ -1216,6 +1650,7 @@ impl Component for Model {
                 prop.byte_array = new_property.byte_array;
                 prop.min_items = new_property.min_items;
                 prop.max_items = new_property.max_items;
+                prop.content_media_type = new_property.content_media_type;
                 prop.min_properties = new_property.min_properties;
                 prop.max_properties = new_property.max_properties;
             }
@@ -1260,6 +1695,9 @@ impl Component for Model {
             Msg::UpdateArrayPropertyMaxItems(doc_index, prop_index, max_items) => {
                 self.document_types[doc_index].properties[prop_index].max_items = Some(max_items);
             }
+            Msg::UpdateArrayPropertyContentMediaType(doc_index, prop_index, content_media_type) => {
+                self.document_types[doc_index].properties[prop_index].content_media_type = Some(content_media_type);
+            }
             Msg::UpdateObjectPropertyMinProperties(doc_index, prop_index, min_properties) => {
                 self.document_types[doc_index].properties[prop_index].min_properties = Some(min_properties);
             }
@@ -1287,6 +1725,7 @@ impl Component for Model {
                     maximum: None,
                     min_items: None,
                     max_items: None,
+                    content_media_type: None,
                     min_properties: None,
                     max_properties: None,
                 };
@@ -1305,9 +1744,15 @@ impl Component for Model {
                     property_vec.remove(rec_prop_index);
                 }
             }
+            Msg::DuplicateRecProperty(doc_index, prop_index, rec_prop_index) => {
+                self.duplicate_rec_property(doc_index, prop_index, rec_prop_index);
+            }
             Msg::UpdateRecPropertyName(doc_index, prop_index, rec_prop_index, name) => {
-                if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
-                    property_vec[rec_prop_index].name = name;
+                let document_type = &mut self.document_types[doc_index];
+                let (document_type_name, parent) = (document_type.name.clone(), document_type.properties[prop_index].name.clone());
+                if let Some(property_vec) = document_type.properties[prop_index].properties.as_mut() {
+                    let old = std::mem::replace(&mut property_vec[rec_prop_index].name, name.clone());
+                    self.rename_property_references(&document_type_name, &format!("{}.{}", parent, old), &format!("{}.{}", parent, name));
                 }
             }
             Msg::UpdateRecPropertyType(doc_index, prop_index, rec_prop_index, data_type) => {
@@ -1384,6 +1829,11 @@ impl Component for Model {
                     property_vec[rec_prop_index].max_items = Some(max_items);
                 }
             }
+            Msg::UpdateArrayRecPropertyContentMediaType(doc_index, prop_index, rec_prop_index, content_media_type) => {
+                if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
+                    property_vec[rec_prop_index].content_media_type = Some(content_media_type);
+                }
+            }
             Msg::UpdateObjectRecPropertyMinProperties(doc_index, prop_index, rec_prop_index, min_props) => {
                 if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
                     property_vec[rec_prop_index].min_properties = Some(min_props);
@@ -1396,54 +1846,283 @@ impl Component for Model {
             }
 
             // Import
-            Msg::UpdateImportedJson(import) => {
-                self.imported_json = import;
+            Msg::UpdateJsonEditor(text) => {
+                self.edit_json(text);
+            }
+
+            // State transition
+            Msg::UpdateTransitionIdentityId(identity_id) => {
+                self.transition_identity_id = identity_id;
+            }
+            Msg::UpdateTransitionKeyId(key_id) => {
+                self.transition_key_id = key_id;
+            }
+            Msg::UpdateTransitionPrivateKey(private_key) => {
+                self.transition_private_key = private_key;
+            }
+            Msg::UpdateTransitionEncoding(encoding) => {
+                self.transition_encoding = encoding;
+            }
+            Msg::BuildCreateTransition => {
+                self.build_create_transition();
+            }
+            Msg::UpdateBaseContractJson(base_contract_json) => {
+                self.base_contract_json = base_contract_json;
+            }
+            Msg::LoadBaseContract => {
+                self.load_base_contract();
+            }
+            Msg::CheckUpdate => {
+                self.check_update();
+            }
+            Msg::BuildUpdateTransition => {
+                self.build_update_transition();
+            }
+
+            // Compare
+            Msg::UpdateCompareJson(compare_json) => {
+                self.compare_json = compare_json;
+            }
+            Msg::Compare => {
+                self.compare();
+            }
+
+            // Sample documents
+            Msg::UpdateSampleDocumentType(document_type) => {
+                self.sample_document_type = document_type;
+            }
+            Msg::UpdateSampleDocuments(documents) => {
+                self.sample_documents_input = documents;
+            }
+            Msg::ValidateSampleDocuments => {
+                self.validate_sample_documents();
+            }
+            Msg::UpdateSampleMode(mode) => {
+                self.sample_mode = mode;
+            }
+            Msg::GenerateSampleDocuments => {
+                self.generate_sample_documents();
+            }
+            Msg::UseGeneratedSamples => {
+                self.use_generated_samples();
             }
-            Msg::Import => {
-                self.parse_imported_json();
+
+            // Export
+            Msg::UpdateExportFormat(format) => {
+                self.export_format = format;
+            }
+            Msg::Export => {
+                self.export();
+            }
+
+            // JSON Schema
+            Msg::UpdateJsonSchemaInput(input) => {
+                self.json_schema_input = input;
+            }
+            Msg::ImportJsonSchema => {
+                self.import_json_schema();
+            }
+
+            // YAML
+            Msg::UpdateYamlInput(input) => {
+                self.yaml_input = input;
+            }
+            Msg::ImportYaml => {
+                self.import_yaml();
+            }
+
+            // Share
+            Msg::Share => {
+                self.share();
+            }
+
+            // Projects
+            Msg::OpenProject(id) => {
+                self.open_project(id);
+            }
+            Msg::UpdateProjectName(name) => {
+                self.project_name = name;
+            }
+            Msg::NewProject => {
+                self.new_project();
+            }
+            Msg::RenameProject => {
+                self.rename_project();
+            }
+            Msg::DuplicateProject => {
+                self.duplicate_project();
+            }
+            Msg::DeleteProject => {
+                self.delete_project();
+            }
+
+            // History
+            Msg::Undo => {
+                self.undo();
+            }
+            Msg::Redo => {
+                self.redo();
+            }
+            Msg::JumpToHistory(position) => {
+                self.jump_to_history(position);
+            }
+
+            // Files
+            Msg::UploadFiles(files) => {
+                self.upload_files(files, ctx.link());
+            }
+            Msg::FileLoaded(name, bytes) => {
+                self.file_loaded(name, bytes);
+            }
+            Msg::Download(download) => {
+                self.download(download);
+            }
+
+            // Templates
+            Msg::UseTemplate(index) => {
+                self.use_template(index);
             }
-            Msg::Clear => {
-                self.json_object = vec![];
-                self.imported_json = String::new();
+            Msg::UseUserTemplate(index) => {
+                self.use_user_template(index);
             }
+            Msg::UpdateTemplateName(name) => {
+                self.template_name = name;
+            }
+            Msg::SaveTemplate => {
+                self.save_template();
+            }
+            Msg::DeleteUserTemplate(index) => {
+                self.delete_user_template(index);
+            }
+
+            // Bulk properties
+            Msg::OpenBulkEntry(doc_index) => {
+                self.bulk_document_type = Some(doc_index);
+            }
+            Msg::CloseBulkEntry => {
+                self.bulk_document_type = None;
+            }
+            Msg::UpdateBulkInput(input) => {
+                self.bulk_input = input;
+            }
+            Msg::ApplyBulkEntry => {
+                self.apply_bulk_entry();
+            }
+
+            // Navigator
+            Msg::UpdateNavigatorSearch(search) => {
+                self.navigator_search = search;
+            }
+            Msg::ToggleCollapsed(doc_index) => {
+                self.toggle_collapsed(doc_index);
+            }
+            Msg::JumpTo(doc_index, id) => {
+                self.jump_to(doc_index, id);
+            }
+
+            // JSON view
+            Msg::UpdateJsonFormat(format) => {
+                self.json_format = format;
+            }
+            Msg::ToggleJsonFold(path) => {
+                self.toggle_json_fold(path);
+            }
+            Msg::SelectJsonNode(path) => {
+                self.select_json_node(path);
+            }
+
+            // Diagram
+            Msg::UpdateReference(document_type, property, target) => {
+                self.update_reference(document_type, property, target);
+            }
+
+            // Query planner
+            Msg::UpdateQueryDocumentType(document_type) => {
+                self.query_document_type = document_type;
+            }
+            Msg::UpdateQueryInput(input) => {
+                self.query_input = input;
+            }
+            Msg::PlanQuery => {
+                self.plan_query();
+            }
+
+            // Index suggestions
+            Msg::UpdateIndexQueries(input) => {
+                self.index_queries_input = input;
+            }
+            Msg::SuggestIndices => {
+                self.suggest_indices();
+            }
+            Msg::AddSuggestedIndices => {
+                self.add_suggested_indices();
+            }
+        }
+        if !from_json_editor {
+            self.sync_json_editor();
         }
+        if self.current_project == project {
+            self.history.record(step, &self.document_types, &self.references);
+        } else {
+            self.history = History::new(&self.document_types, &self.references);
+            self.collapsed.clear();
+            self.bulk_document_type = None;
+        }
+        self.autosave();
         true
     }
 
+    fn rendered(&mut self, _ctx: &yew::Context<Self>, _first_render: bool) {
+        if let Some(id) = self.pending_jump.take() {
+            navigator::scroll_to(&id);
+        }
+    }
+
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
 
         let s = &self.json_object.join(",");
         let new_s = format!("{{{}}}", s);
         let json_obj: serde_json::Value = serde_json::from_str(&new_s).unwrap();
-        let json_pretty = serde_json::to_string_pretty(&json_obj).unwrap();
+        let validated = self.validated_json.as_ref() == Some(&self.json_object);
                                 
-        let textarea = if self.json_object.len() != 0 {
-            html! {
-                <textarea class="textarea" id="json_output" value={if self.json_object.len() != 0 as usize {
-                    serde_json::to_string(&json_obj).unwrap()
-                } else { 
-                    "".to_string()
-                }}>
-                </textarea>
-            }
-        } else {
-            html! {}
-        };
-
         // html
         html! {
-            <main class="home">
+            <main class="home with-sidebar" onkeydown={ctx.link().batch_callback(|e: KeyboardEvent| {
+                // Ctrl+Z undoes, Ctrl+Shift+Z and Ctrl+Y redo (Cmd on macOS). Text fields keep their own undo.
+                if !(e.ctrl_key() || e.meta_key()) || history::is_text_field(e.target()) {
+                    return None;
+                }
+                let msg = match e.key().to_lowercase().as_str() {
+                    "z" if e.shift_key() => Msg::Redo,
+                    "z" => Msg::Undo,
+                    "y" => Msg::Redo,
+                    _ => return None,
+                };
+                e.prevent_default();
+                Some(msg)
+            })}>
+            // find and jump to document types, properties and indices
+            {self.view_navigator(ctx)}
             <img class="logo" src="https://media.dash.org/wp-content/uploads/dash-logo.svg" alt="Dash logo" width="200" height="100" />
             <br/><br/>
             <h1 class="header">{"Data Contract Creator"}</h1>
             <h3 class="instructions">{"Instructions:"}</h3>
             <ul class="instructions-text">
                 <li><div>{"Use the left column to build, edit, and submit a data contract."}</div></li>
-                <li><div>{"Use the right column to copy the generated data contract to your clipboard or import."}</div></li>
+                <li><div>{"Use the right column to edit the generated data contract as JSON, copy it to your clipboard, or paste one to import."}</div></li>
             </ul>
             <body>
             <div class="column-left">
 
+                // switch between saved contracts
+                {self.view_projects(ctx)}
+
+                // start from a template
+                {self.view_templates(ctx)}
+
+                // undo and redo edits
+                {self.view_history(ctx)}
+
                 // show input fields
                 <p class="input-fields">{self.view_document_types(ctx)}</p>
 
@@ -1451,37 +2130,43 @@ impl Component for Model {
                     // add input fields for another document type and add one to Self::document_types
                     <button class="button2" onclick={ctx.link().callback(|_| Msg::AddDocumentType)}>{"Add document type"}</button><br/>
 
-                    // look at document_types and generate json object from it
+                    // validate the contract generated from document_types
                     <button class="button button-primary" onclick={ctx.link().callback(|_| Msg::Submit)}>{"Submit"}</button>
+
+                    // put the document types in the URL to share them
+                    {self.view_share(ctx)}
                 </div>
                 <div class="footnotes">
                 </div>
             </div>
             <div class="column-right">
             
-                // format and display json object
-                <p class="output-container">
+                // format and display json object, and accept dropped contract files
+                <p class="output-container" ondragover={|e: DragEvent| e.prevent_default()} ondrop={ctx.link().callback(|e: DragEvent| Msg::UploadFiles(files::dropped_files(&e)))}>
                     <h2>{"Contract"}</h2>
-                    <h3>{if self.imported_json.len() == 0 && self.error_messages.len() != 0 {"Validation errors:"} else {""}}</h3>
+                    <h3>{if validated && self.error_messages.len() != 0 {"Validation errors:"} else {""}}</h3>
                     <div>{
-                        if self.imported_json.len() == 0 && self.error_messages.len() != 0 {
+                        if validated && self.error_messages.len() != 0 {
                             html! {
                                 <ul class="error-text">
                                     { for self.error_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
                                 </ul>
                             }
-                        } else if self.imported_json.len() == 0 && self.error_messages.len() == 0 &&self.json_object.len() > 0 { 
+                        } else if validated && self.json_object.len() > 0 {
                             html! {<p class="passed-text">{"Validation passed ✓"}</p>}
                         } else {
                             html! {""}
                         }
-                    }</div>                    
-                    <h3>{if self.json_object.len() != 0 {"With whitespace:"} else {""}}</h3>
+                    }</div>
+                    <h3>{"With whitespace:"}</h3>
                     <pre>
-                    <textarea class="textarea" id="json_output" placeholder="Paste here to import" value={if self.json_object.len() == 0 {self.imported_json.clone()} else {json_pretty}} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateImportedJson(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                    <textarea class="textarea" id="json_editor" placeholder="Type or paste the document schemas or a full contract" value={self.json_editor.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateJsonEditor(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
                     </pre>
-                    <h3>{if self.json_object.len() != 0 {"Without whitespace:"} else {""}}</h3>
-                    <pre>{textarea}</pre>
+                    <ul class="error-text">
+                        { for self.json_editor_errors.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                    </ul>
+                    <h3>{"Code view:"}</h3>
+                    {self.view_json_code(ctx)}
                     <p><b>
                     {
                         if serde_json::to_string(&json_obj).unwrap().len() > 2 {
@@ -1489,9 +2174,30 @@ impl Component for Model {
                         } else {String::from("Size: 0 bytes")}
                     }
                     </b></p>
-                    <div><button class="button-import" onclick={ctx.link().callback(|_| Msg::Import)}>{"Import"}</button></div>
-                    <div><button class="button-clear" onclick={ctx.link().callback(|_| Msg::Clear)}>{"Clear"}</button></div>
+                    <div>
+                        {self.view_file_upload(ctx)}
+                        <button class="button2" onclick={ctx.link().callback(|_| Msg::Download(files::Download::ContractJson))}>{"Download JSON"}</button>
+                        <button class="button2" onclick={ctx.link().callback(|_| Msg::Download(files::Download::ContractCbor))}>{"Download CBOR"}</button>
+                    </div>
                 </p>
+                <br/>
+                {self.view_transition(ctx)}
+                <br/>
+                {self.view_diff(ctx)}
+                <br/>
+                {self.view_diagram(ctx)}
+                <br/>
+                {self.view_sample_documents(ctx)}
+                <br/>
+                {self.view_query_planner(ctx)}
+                <br/>
+                {self.view_generated_samples(ctx)}
+                <br/>
+                {self.view_export(ctx)}
+                <br/>
+                {self.view_json_schema_import(ctx)}
+                <br/>
+                {self.view_yaml_import(ctx)}
             </div>
             </body>
             </main>
@@ -1499,6 +2205,13 @@ impl Component for Model {
     }
 }
 
+/// Builds the DPP factory used for validating contracts and creating state transitions
+fn data_contract_factory() -> DataContractFactory {
+    let protocol_version_validator = dpp::version::ProtocolVersionValidator::default();
+    let data_contract_validator = dpp::data_contract::validation::data_contract_validator::DataContractValidator::new(Arc::new(protocol_version_validator));
+    DataContractFactory::new(1, Arc::new(data_contract_validator))
+}
+
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::Renderer::<Model>::new().render();
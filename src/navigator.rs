@@ -0,0 +1,162 @@
+//! Collapsible tree of document types, properties and indices with search and jump-to
+
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{DataType, Model, Msg, Property};
+
+pub fn document_type_id(doc_index: usize) -> String {
+    format!("document-type-{}", doc_index)
+}
+
+pub fn property_id(doc_index: usize, prop_index: usize) -> String {
+    format!("document-type-{}-property-{}", doc_index, prop_index)
+}
+
+pub fn rec_property_id(doc_index: usize, prop_index: usize, rec_prop_index: usize) -> String {
+    format!("document-type-{}-property-{}-{}", doc_index, prop_index, rec_prop_index)
+}
+
+pub fn index_id(doc_index: usize, index_index: usize) -> String {
+    format!("document-type-{}-index-{}", doc_index, index_index)
+}
+
+fn type_label(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::String => "string",
+        DataType::Integer => "integer",
+        DataType::Array => "array",
+        DataType::Object => "object",
+        DataType::Number => "number",
+        DataType::Boolean => "boolean",
+    }
+}
+
+/// Whether a property's name, type or description contains the lowercase search text
+fn property_matches(property: &Property, search: &str) -> bool {
+    property.name.to_lowercase().contains(search)
+        || type_label(&property.data_type).contains(search)
+        || property.description.as_deref().unwrap_or_default().to_lowercase().contains(search)
+}
+
+/// Scrolls the editor to the element with the given ID and focuses it, or the first input inside it
+pub fn scroll_to(id: &str) {
+    if let Some(element) = web_sys::window().and_then(|window| window.document()).and_then(|document| document.get_element_by_id(id)) {
+        element.scroll_into_view();
+        let input = if element.tag_name().eq_ignore_ascii_case("input") { Some(element) } else { element.query_selector("input").ok().flatten() };
+        if let Some(input) = input.and_then(|input| input.dyn_into::<HtmlElement>().ok()) {
+            let _ = input.focus();
+        }
+    }
+}
+
+impl Model {
+    /// Keeps the collapsed state and the open bulk entry on the same document types after the list
+    /// changes. `new_position` maps a document type's old position to its new one, or to None if it's gone.
+    pub fn remap_document_types(&mut self, new_position: impl Fn(usize) -> Option<usize>) {
+        self.collapsed = self.collapsed.iter().filter_map(|&i| new_position(i)).collect();
+        self.bulk_document_type = self.bulk_document_type.and_then(&new_position);
+    }
+
+    /// Remaps by name after the document types were replaced as a whole, as by undo or a JSON edit
+    pub fn remap_document_types_by_name(&mut self, old_names: &[String]) {
+        let new_names = self.document_types.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+        self.remap_document_types(|i| old_names.get(i).and_then(|name| new_names.iter().position(|n| n == name)));
+    }
+
+    /// Collapses or expands a document type in the tree and the editor
+    pub fn toggle_collapsed(&mut self, doc_index: usize) {
+        if !self.collapsed.remove(&doc_index) {
+            self.collapsed.insert(doc_index);
+        }
+    }
+
+    /// Expands the document type so the element exists, and scrolls to it after the next render
+    pub fn jump_to(&mut self, doc_index: usize, id: String) {
+        self.collapsed.remove(&doc_index);
+        self.pending_jump = Some(id);
+    }
+
+    fn view_tree_link(&self, ctx: &yew::Context<Self>, doc_index: usize, id: String, label: String, detail: &str) -> Html {
+        html! {
+            <li>
+                <a href={format!("#{}", id)} onclick={ctx.link().callback(move |e: yew::MouseEvent| {
+                    e.prevent_default();
+                    Msg::JumpTo(doc_index, id.clone())
+                })}>{if label.is_empty() { String::from("(unnamed)") } else { label }}</a>
+                <span class="tree-detail">{format!(" {}", detail)}</span>
+            </li>
+        }
+    }
+
+    fn view_tree_document_type(&self, ctx: &yew::Context<Self>, doc_index: usize) -> Html {
+        let search = self.navigator_search.trim().to_lowercase();
+        let document_type = &self.document_types[doc_index];
+        let name_matches = document_type.name.to_lowercase().contains(&search);
+        // A matching document type lists everything, otherwise only the matching properties and indices
+        let show_property = |property: &Property| name_matches || property_matches(property, &search);
+        let properties = document_type.properties.iter().enumerate().filter(|(_, property)| {
+            show_property(property) || property.properties.as_ref().map_or(false, |nested| nested.iter().any(|p| property_matches(p, &search)))
+        }).collect::<Vec<_>>();
+        let indices = document_type.indices.iter().enumerate().filter(|(_, index)| name_matches || index.name.to_lowercase().contains(&search)).collect::<Vec<_>>();
+        if !name_matches && properties.is_empty() && indices.is_empty() {
+            return html! {};
+        }
+        // Searching expands every document type with matches, so the toggle does nothing until the search is cleared
+        let searching = !search.is_empty();
+        let expanded = searching || !self.collapsed.contains(&doc_index);
+        html! {
+            <li>
+                <button class="tree-toggle" disabled={searching} title={if searching { "Clear the search to collapse" } else if expanded { "Collapse" } else { "Expand" }} onclick={ctx.link().callback(move |_| Msg::ToggleCollapsed(doc_index))}>{if expanded { "▾" } else { "▸" }}</button>
+                <a href={format!("#{}", document_type_id(doc_index))} onclick={ctx.link().callback(move |e: yew::MouseEvent| {
+                    e.prevent_default();
+                    Msg::JumpTo(doc_index, document_type_id(doc_index))
+                })}><b>{if document_type.name.is_empty() { format!("Document type {}", doc_index + 1) } else { document_type.name.clone() }}</b></a>
+                {
+                    if expanded {
+                        html! {
+                            <ul>
+                                {for properties.into_iter().map(|(prop_index, property)| html! {
+                                    <>
+                                    {self.view_tree_link(ctx, doc_index, property_id(doc_index, prop_index), property.name.clone(), type_label(&property.data_type))}
+                                    {
+                                        match &property.properties {
+                                            Some(nested) if !nested.is_empty() => html! {
+                                                <ul>
+                                                    {for nested.iter().enumerate().filter(|(_, p)| show_property(property) || property_matches(p, &search)).map(|(rec_prop_index, p)| {
+                                                        self.view_tree_link(ctx, doc_index, rec_property_id(doc_index, prop_index, rec_prop_index), p.name.clone(), type_label(&p.data_type))
+                                                    })}
+                                                </ul>
+                                            },
+                                            _ => html! {},
+                                        }
+                                    }
+                                    </>
+                                })}
+                                {for indices.into_iter().map(|(index_index, index)| {
+                                    self.view_tree_link(ctx, doc_index, index_id(doc_index, index_index), index.name.clone(), if index.unique { "unique index" } else { "index" })
+                                })}
+                            </ul>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </li>
+        }
+    }
+
+    /// Sidebar tree of the contract. The search filters by name, type or description.
+    pub fn view_navigator(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <aside class="sidebar navigator">
+                <h2>{"Navigator"}</h2>
+                <input type="text" placeholder="Search by name, type or description" value={self.navigator_search.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateNavigatorSearch(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <ul class="tree">
+                    {for (0..self.document_types.len()).map(|i| self.view_tree_document_type(ctx, i))}
+                </ul>
+            </aside>
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! Query planner: checks which index serves a document query the way Drive does
+
+use serde_json::Value;
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, InputEvent, TargetCast};
+
+use crate::{DocumentType, Index, Model, Msg};
+
+/// Fields every document has
+const SYSTEM_FIELDS: [&str; 4] = ["$id", "$ownerId", "$createdAt", "$updatedAt"];
+/// Most results and `in` values Drive accepts
+const MAX_LIMIT: u64 = 100;
+const MAX_IN_VALUES: usize = 100;
+
+/// The where clauses and ordering of a query, grouped the way an index is matched against them
+#[derive(Debug, Default)]
+pub struct Query {
+    /// Fields compared with `==`
+    pub equal: Vec<String>,
+    /// Field of the `in` clause
+    pub in_field: Option<String>,
+    /// Field of the range clauses: `<`, `<=`, `>`, `>=` and `startsWith`
+    pub range: Option<String>,
+    /// orderBy fields, true for ascending
+    pub order_by: Vec<(String, bool)>,
+}
+
+impl Query {
+    /// Drive serves queries without where clauses or ordering, and lookups by `$id`, from the primary key
+    pub fn uses_primary_key(&self) -> bool {
+        let by_id = self.equal == ["$id"] || self.equal.is_empty();
+        by_id && self.in_field.is_none() && self.range.is_none() && self.order_by.is_empty()
+    }
+}
+
+/// Whether a declared index serves the query, and why not
+pub struct IndexResult {
+    pub name: String,
+    pub reasons: Vec<String>,
+}
+
+pub struct QueryPlan {
+    /// Why the query is invalid regardless of indices
+    pub errors: Vec<String>,
+    /// Whether an index or the primary key serves the query
+    pub served: bool,
+    pub summary: String,
+    pub indices: Vec<IndexResult>,
+}
+
+/// Names a query can filter and sort by: system fields and properties, dotted for nested ones
+fn queryable_fields(document_type: &DocumentType) -> Vec<String> {
+    let mut fields = SYSTEM_FIELDS.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+    for property in &document_type.properties {
+        fields.push(property.name.clone());
+        for nested in property.properties.iter().flat_map(|p| p.iter()) {
+            fields.push(format!("{}.{}", property.name, nested.name));
+        }
+    }
+    fields
+}
+
+fn check_field(field: &str, fields: &[String], errors: &mut Vec<String>) {
+    if !fields.iter().any(|f| f == field) {
+        errors.push(format!("\"{}\" is not a property of the document type", field));
+    }
+}
+
+fn parse_where(clauses: &Value, fields: &[String], query: &mut Query, errors: &mut Vec<String>) {
+    let clauses = match clauses.as_array() {
+        Some(clauses) => clauses,
+        None => return errors.push(String::from("where must be an array of [field, operator, value] clauses")),
+    };
+    // Bounds on the range field: lower, upper and startsWith
+    let (mut lower, mut upper, mut starts_with) = (0, 0, 0);
+    for clause in clauses {
+        let (field, operator, value) = match clause.as_array().map(|c| c.as_slice()) {
+            Some([Value::String(field), Value::String(operator), value]) => (field.as_str(), operator.as_str(), value),
+            _ => {
+                errors.push(format!("{} is not a [field, operator, value] clause", clause));
+                continue;
+            }
+        };
+        check_field(field, fields, errors);
+        match operator {
+            "==" => {
+                if query.equal.iter().any(|f| f == field) {
+                    errors.push(format!("\"{}\" has more than one == clause", field));
+                } else {
+                    query.equal.push(field.to_string());
+                }
+            }
+            "in" => {
+                if query.in_field.is_some() {
+                    errors.push(String::from("A query can only have one in clause"));
+                }
+                query.in_field = Some(field.to_string());
+                match value.as_array() {
+                    Some(values) if values.is_empty() => errors.push(format!("The in clause on \"{}\" needs at least one value", field)),
+                    Some(values) if values.len() > MAX_IN_VALUES => errors.push(format!("The in clause on \"{}\" has {} values, more than {}", field, values.len(), MAX_IN_VALUES)),
+                    Some(values) if values.iter().enumerate().any(|(i, v)| values[..i].contains(v)) => errors.push(format!("The in clause on \"{}\" has duplicate values", field)),
+                    Some(_) => {}
+                    None => errors.push(format!("The in clause on \"{}\" needs an array of values", field)),
+                }
+            }
+            "<" | "<=" | ">" | ">=" | "startsWith" => {
+                match &query.range {
+                    Some(range) if range != field => errors.push(format!("Range clauses must all be on one field, but the query has ranges on \"{}\" and \"{}\"", range, field)),
+                    _ => query.range = Some(field.to_string()),
+                }
+                match operator {
+                    ">" | ">=" => lower += 1,
+                    "<" | "<=" => upper += 1,
+                    _ => starts_with += 1,
+                }
+                if operator == "startsWith" {
+                    if value.as_str().map_or(true, |s| s.is_empty()) {
+                        errors.push(format!("startsWith on \"{}\" needs a non-empty string", field));
+                    }
+                } else if !(value.is_number() || value.is_string()) {
+                    errors.push(format!("{} on \"{}\" needs a number or a string", operator, field));
+                }
+            }
+            _ => errors.push(format!("Unknown operator \"{}\". Use ==, <, <=, >, >=, in or startsWith", operator)),
+        }
+    }
+    if lower > 1 || upper > 1 || starts_with > 1 || (starts_with > 0 && lower + upper > 0) {
+        errors.push(String::from("The range field can have at most one lower bound and one upper bound, and startsWith can't be combined with other bounds"));
+    }
+    for field in query.equal.iter() {
+        if query.in_field.as_ref() == Some(field) || query.range.as_ref() == Some(field) {
+            errors.push(format!("\"{}\" has an == clause and another clause", field));
+        }
+    }
+    if query.in_field.is_some() && query.in_field == query.range {
+        errors.push(String::from("The in field can't also have a range"));
+    }
+}
+
+fn parse_order_by(order_by: &Value, fields: &[String], query: &mut Query, errors: &mut Vec<String>) {
+    let clauses = match order_by.as_array() {
+        Some(clauses) => clauses,
+        None => return errors.push(String::from("orderBy must be an array of [field, \"asc\" or \"desc\"] clauses")),
+    };
+    for clause in clauses {
+        match clause.as_array().map(|c| c.as_slice()) {
+            Some([Value::String(field), Value::String(direction)]) if direction == "asc" || direction == "desc" => {
+                check_field(field, fields, errors);
+                if query.order_by.iter().any(|(f, _)| f == field) {
+                    errors.push(format!("\"{}\" appears more than once in orderBy", field));
+                } else {
+                    query.order_by.push((field.clone(), direction == "asc"));
+                }
+            }
+            _ => errors.push(format!("{} is not a [field, \"asc\" or \"desc\"] clause", clause)),
+        }
+    }
+}
+
+/// Parses a query in the shape the SDK sends to Drive, reporting what Drive would reject
+pub fn parse_query(input: &str, document_type: &DocumentType) -> Result<Query, Vec<String>> {
+    let value: Value = serde_json::from_str(input).map_err(|e| vec![format!("Invalid JSON: {}", e)])?;
+    let object = value.as_object().ok_or_else(|| vec![String::from("The query must be a JSON object")])?;
+    let fields = queryable_fields(document_type);
+    let mut query = Query::default();
+    let mut errors = vec![];
+    for (key, value) in object {
+        match key.as_str() {
+            "where" => parse_where(value, &fields, &mut query, &mut errors),
+            "orderBy" => parse_order_by(value, &fields, &mut query, &mut errors),
+            "limit" => match value.as_u64() {
+                Some(limit) if (1..=MAX_LIMIT).contains(&limit) => {}
+                _ => errors.push(format!("limit must be a whole number from 1 to {}", MAX_LIMIT)),
+            },
+            "startAfter" | "startAt" => {
+                let id = value.as_str().and_then(|s| bs58::decode(s).into_vec().ok());
+                if id.map_or(true, |bytes| bytes.len() != 32) {
+                    errors.push(format!("{} must be a base58 document ID", key));
+                }
+            }
+            _ => errors.push(format!("Unknown query field \"{}\". Use where, orderBy, limit, startAfter or startAt", key)),
+        }
+    }
+    if object.contains_key("startAfter") && object.contains_key("startAt") {
+        errors.push(String::from("Use either startAfter or startAt, not both"));
+    }
+    if let Some(range) = &query.range {
+        if !query.order_by.iter().any(|(f, _)| f == range) {
+            errors.push(format!("A range on \"{}\" needs \"{}\" in orderBy", range, range));
+        }
+    }
+    if errors.is_empty() { Ok(query) } else { Err(errors) }
+}
+
+/// Why an index can't serve the query, empty if it can. The index is walked from its first
+/// property: first the == fields in any order, then the in field, then the range field, and the
+/// orderBy fields must follow the same order, all in the index's direction or all reversed.
+pub fn check_index(index: &Index, query: &Query) -> Vec<String> {
+    let properties = index.properties.iter().map(|p| (p.0.as_str(), p.1 == "asc")).collect::<Vec<_>>();
+    let names = properties.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let needed = query.equal.iter().chain(&query.in_field).chain(&query.range).chain(query.order_by.iter().map(|(f, _)| f));
+    let mut missing = vec![];
+    for field in needed {
+        if !names.contains(&field.as_str()) && !missing.contains(field) {
+            missing.push(field.clone());
+        }
+    }
+    if !missing.is_empty() {
+        return missing.into_iter().map(|field| format!("The index doesn't include \"{}\"", field)).collect();
+    }
+
+    let equal_count = query.equal.len();
+    if let Some(name) = names[..equal_count].iter().find(|name| !query.equal.iter().any(|f| f == *name)) {
+        return vec![format!("\"{}\" comes before the == fields in the index, but the query doesn't fix it with ==", name)];
+    }
+    let rest = &names[equal_count..];
+    let mut position = 0;
+    for (kind, field) in [("in", &query.in_field), ("range", &query.range)] {
+        if let Some(field) = field {
+            if rest.get(position) != Some(&field.as_str()) {
+                let found = rest.get(position).copied().unwrap_or_default();
+                return vec![format!("The {} field \"{}\" must come next in the index after the == fields, but the index has \"{}\" there", kind, field, found)];
+            }
+            position += 1;
+        }
+    }
+
+    let order = query.order_by.iter().filter(|(f, _)| !query.equal.contains(f)).collect::<Vec<_>>();
+    let index_order = &properties[equal_count..];
+    if order.iter().zip(index_order).any(|((f, _), (name, _))| f != name) || order.len() > index_order.len() {
+        let expected = index_order.iter().take(order.len().max(1)).map(|(name, _)| *name).collect::<Vec<_>>().join(", ");
+        let actual = order.iter().map(|(f, _)| f.as_str()).collect::<Vec<_>>().join(", ");
+        return vec![format!("orderBy ({}) must follow the index order after the == fields ({})", actual, expected)];
+    }
+    let same = order.iter().zip(index_order).filter(|((_, asc), (_, index_asc))| asc == index_asc).count();
+    if same != 0 && same != order.len() {
+        return vec![String::from("orderBy directions must all match the index directions or all be reversed")];
+    }
+    vec![]
+}
+
+/// Checks the query against every index of the document type
+pub fn plan(input: &str, document_type: &DocumentType) -> QueryPlan {
+    let query = match parse_query(input, document_type) {
+        Ok(query) => query,
+        Err(errors) => return QueryPlan { errors, served: false, summary: String::from("Drive would reject this query"), indices: vec![] },
+    };
+    if query.uses_primary_key() {
+        return QueryPlan { errors: vec![], served: true, summary: String::from("Served by the primary key ($id) without an index"), indices: vec![] };
+    }
+    let indices = document_type
+        .indices
+        .iter()
+        .map(|index| IndexResult { name: index.name.clone(), reasons: check_index(index, &query) })
+        .collect::<Vec<_>>();
+    let serving = indices.iter().find(|result| result.reasons.is_empty());
+    let summary = match serving {
+        Some(result) => format!("Served by the index \"{}\"", result.name),
+        None if indices.is_empty() => format!("No index serves this query: \"{}\" has no indices", document_type.name),
+        None => String::from("No index serves this query"),
+    };
+    let served = serving.is_some();
+    QueryPlan { errors: vec![], served, summary, indices }
+}
+
+impl Model {
+    /// Name of the document type queries are planned and indices suggested for
+    pub fn selected_query_document_type(&self) -> String {
+        if self.query_document_type.is_empty() {
+            self.document_types.first().map(|doc_type| doc_type.name.clone()).unwrap_or_default()
+        } else {
+            self.query_document_type.clone()
+        }
+    }
+
+    pub fn plan_query(&mut self) {
+        let name = self.selected_query_document_type();
+        self.query_plan = match self.document_types.iter().find(|d| d.name == name) {
+            Some(document_type) => Some(plan(&self.query_input, document_type)),
+            None => Some(QueryPlan { errors: vec![format!("Unknown document type \"{}\"", name)], served: false, summary: String::new(), indices: vec![] }),
+        };
+    }
+
+    pub fn view_query_planner(&self, ctx: &yew::Context<Self>) -> Html {
+        let selected = self.selected_query_document_type();
+        html! {
+            <p class="output-container">
+                <h2>{"Query planner"}</h2>
+                <h3>{"Document type"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| Msg::UpdateQueryDocumentType(e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                    {for self.document_types.iter().map(|doc_type| html! {
+                        <option value={doc_type.name.clone()} selected={doc_type.name == selected}>{doc_type.name.clone()}</option>
+                    })}
+                </select>
+                <h3>{"Query"}</h3>
+                <textarea class="textarea" placeholder={"{\"where\": [[\"$ownerId\", \"==\", \"...\"]], \"orderBy\": [[\"$createdAt\", \"asc\"]], \"limit\": 10}"} value={self.query_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateQueryInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::PlanQuery)}>{"Check query"}</button></div>
+                {for self.query_plan.iter().map(|plan| html! {
+                    <>
+                    <ul class="error-text">
+                        { for plan.errors.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                    </ul>
+                    {
+                        if plan.summary.is_empty() {
+                            html! {}
+                        } else if plan.served {
+                            html! { <p class="passed-text">{format!("{} ✓", plan.summary)}</p> }
+                        } else {
+                            html! { <p class="error-text">{plan.summary.clone()}</p> }
+                        }
+                    }
+                    {for plan.indices.iter().map(|result| html! {
+                        <>
+                        <h3>{format!("Index \"{}\"", result.name)}</h3>
+                        {
+                            if result.reasons.is_empty() {
+                                html! { <p class="passed-text">{"Serves the query ✓"}</p> }
+                            } else {
+                                html! {
+                                    <ul class="error-text">
+                                        { for result.reasons.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                                    </ul>
+                                }
+                            }
+                        }
+                        </>
+                    })}
+                    </>
+                })}
+                {self.view_index_suggestions(ctx)}
+            </p>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataType, IndexProperties, Property};
+
+    fn document_type() -> DocumentType {
+        let property = |name: &str, data_type: DataType| Property { name: name.to_string(), data_type, ..Default::default() };
+        let mut meta = property("meta", DataType::Object);
+        meta.properties = Some(Box::new(vec![property("tag", DataType::String)]));
+        DocumentType {
+            name: String::from("note"),
+            properties: vec![property("title", DataType::String), property("score", DataType::Integer), meta],
+            ..Default::default()
+        }
+    }
+
+    fn index(properties: &[(&str, &str)]) -> Index {
+        Index { name: String::from("index"), properties: properties.iter().map(|(name, direction)| IndexProperties(name.to_string(), direction.to_string())).collect(), unique: false }
+    }
+
+    fn query(input: &str) -> Query {
+        parse_query(input, &document_type()).unwrap()
+    }
+
+    #[test]
+    fn parse_query_groups_clauses() {
+        let query = query(r#"{"where": [["$ownerId", "==", "x"], ["meta.tag", "in", ["a", "b"]], ["score", ">", 1]], "orderBy": [["score", "desc"]], "limit": 10}"#);
+        assert_eq!(query.equal, ["$ownerId"]);
+        assert_eq!(query.in_field.as_deref(), Some("meta.tag"));
+        assert_eq!(query.range.as_deref(), Some("score"));
+        assert_eq!(query.order_by, [(String::from("score"), false)]);
+        assert!(!query.uses_primary_key());
+    }
+
+    #[test]
+    fn parse_query_rejects_what_drive_rejects() {
+        let errors = |input: &str| parse_query(input, &document_type()).unwrap_err();
+        assert_eq!(errors(r#"{"where": [["body", "==", 1]]}"#), ["\"body\" is not a property of the document type"]);
+        assert_eq!(errors(r#"{"where": [["score", ">", 1]]}"#), ["A range on \"score\" needs \"score\" in orderBy"]);
+        assert_eq!(errors(r#"{"limit": 101}"#), ["limit must be a whole number from 1 to 100"]);
+        assert_eq!(errors(r#"{"where": [["title", "in", []]]}"#), ["The in clause on \"title\" needs at least one value"]);
+        assert!(errors(r#"{"where": [["title", ">", "a"], ["score", "<", 1]], "orderBy": [["title", "asc"]]}"#)[0].starts_with("Range clauses must all be on one field"));
+        assert!(errors("[]")[0].starts_with("The query must be a JSON object"));
+    }
+
+    #[test]
+    fn primary_key_queries() {
+        assert!(query("{}").uses_primary_key());
+        assert!(query(r#"{"where": [["$id", "==", "x"]]}"#).uses_primary_key());
+    }
+
+    #[test]
+    fn check_index_accepts_equal_fields_in_any_order_then_range() {
+        let query = query(r#"{"where": [["title", "==", "a"], ["$ownerId", "==", "x"], ["score", ">", 1]], "orderBy": [["score", "asc"]]}"#);
+        assert!(check_index(&index(&[("$ownerId", "asc"), ("title", "asc"), ("score", "asc")]), &query).is_empty());
+        assert!(check_index(&index(&[("title", "asc"), ("$ownerId", "asc"), ("score", "desc")]), &query).is_empty());
+    }
+
+    #[test]
+    fn check_index_reports_why_it_cannot_serve() {
+        let query = query(r#"{"where": [["$ownerId", "==", "x"]], "orderBy": [["score", "asc"], ["title", "desc"]]}"#);
+        assert_eq!(check_index(&index(&[("$ownerId", "asc")]), &query), ["The index doesn't include \"score\"", "The index doesn't include \"title\""]);
+        assert_eq!(
+            check_index(&index(&[("score", "asc"), ("$ownerId", "asc"), ("title", "asc")]), &query),
+            ["\"score\" comes before the == fields in the index, but the query doesn't fix it with =="]
+        );
+        assert_eq!(
+            check_index(&index(&[("$ownerId", "asc"), ("title", "asc"), ("score", "asc")]), &query),
+            ["orderBy (score, title) must follow the index order after the == fields (title, score)"]
+        );
+        assert_eq!(
+            check_index(&index(&[("$ownerId", "asc"), ("score", "asc"), ("title", "asc")]), &query),
+            ["orderBy directions must all match the index directions or all be reversed"]
+        );
+        assert!(check_index(&index(&[("$ownerId", "asc"), ("score", "desc"), ("title", "asc")]), &query).is_empty());
+    }
+
+    #[test]
+    fn check_index_needs_in_field_before_range() {
+        let query = query(r#"{"where": [["title", "in", ["a"]], ["score", ">", 1]], "orderBy": [["title", "asc"], ["score", "asc"]]}"#);
+        assert!(check_index(&index(&[("title", "asc"), ("score", "asc")]), &query).is_empty());
+        assert_eq!(
+            check_index(&index(&[("score", "asc"), ("title", "asc")]), &query),
+            ["The in field \"title\" must come next in the index after the == fields, but the index has \"score\" there"]
+        );
+    }
+}
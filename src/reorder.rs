@@ -0,0 +1,96 @@
+//! Reordering of document types, properties, indices and index properties
+
+use yew::{html, DragEvent, Html};
+
+use crate::{Model, Msg};
+
+/// A reorderable list in the editor, identified by the indices of its parents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListKind {
+    DocumentTypes,
+    Properties(usize),
+    RecProperties(usize, usize),
+    Indices(usize),
+    IndexProperties(usize, usize),
+}
+
+fn move_within<T>(list: &mut Vec<T>, from: usize, to: usize) {
+    if from < list.len() && to < list.len() && from != to {
+        let item = list.remove(from);
+        list.insert(to, item);
+    }
+}
+
+/// Where the item at `i` ends up after moving the item at `from` to `to`
+fn moved_position(i: usize, from: usize, to: usize) -> usize {
+    if i == from {
+        to
+    } else if from < i && i <= to {
+        i - 1
+    } else if to <= i && i < from {
+        i + 1
+    } else {
+        i
+    }
+}
+
+impl Model {
+    fn list_len(&self, list: ListKind) -> usize {
+        match list {
+            ListKind::DocumentTypes => self.document_types.len(),
+            ListKind::Properties(doc) => self.document_types[doc].properties.len(),
+            ListKind::RecProperties(doc, prop) => self.document_types[doc].properties[prop].properties.as_ref().map_or(0, |p| p.len()),
+            ListKind::Indices(doc) => self.document_types[doc].indices.len(),
+            ListKind::IndexProperties(doc, index) => self.document_types[doc].indices[index].properties.len(),
+        }
+    }
+
+    /// Moves an item within its list. The generated JSON follows the new order.
+    pub fn move_item(&mut self, list: ListKind, from: usize, to: usize) {
+        match list {
+            ListKind::DocumentTypes => {
+                if from < self.document_types.len() && to < self.document_types.len() {
+                    move_within(&mut self.document_types, from, to);
+                    self.remap_document_types(|i| Some(moved_position(i, from, to)));
+                }
+            }
+            ListKind::Properties(doc) => move_within(&mut self.document_types[doc].properties, from, to),
+            ListKind::RecProperties(doc, prop) => {
+                if let Some(properties) = self.document_types[doc].properties[prop].properties.as_mut() {
+                    move_within(properties, from, to);
+                }
+            }
+            ListKind::Indices(doc) => move_within(&mut self.document_types[doc].indices, from, to),
+            ListKind::IndexProperties(doc, index) => move_within(&mut self.document_types[doc].indices[index].properties, from, to),
+        }
+    }
+
+    /// Moves the dragged item to the drop position if it was dropped in its own list
+    pub fn drop_item(&mut self, list: ListKind, to: usize) {
+        if let Some((dragged_list, from)) = self.dragging.take() {
+            if dragged_list == list {
+                self.move_item(list, from, to);
+            } else {
+                self.dragging = Some((dragged_list, from));
+            }
+        }
+    }
+
+    /// Drag handle with up and down buttons for an item. Items are dropped on another item's handle.
+    pub fn view_reorder_handle(&self, ctx: &yew::Context<Self>, list: ListKind, index: usize) -> Html {
+        let len = self.list_len(list);
+        html! {
+            <span class="reorder">
+                <span class="drag-handle" title="Drag to reorder" draggable="true"
+                    ondragstart={ctx.link().callback(move |_: DragEvent| Msg::StartDrag(list, index))}
+                    ondragover={|e: DragEvent| e.prevent_default()}
+                    ondrop={ctx.link().callback(move |e: DragEvent| {
+                        e.prevent_default();
+                        Msg::DropItem(list, index)
+                    })}>{"⠿"}</span>
+                <button class="button2" title="Move up" disabled={index == 0} onclick={ctx.link().callback(move |_| Msg::MoveItem(list, index, index.saturating_sub(1)))}>{"↑"}</button>
+                <button class="button2" title="Move down" disabled={index + 1 >= len} onclick={ctx.link().callback(move |_| Msg::MoveItem(list, index, index + 1))}>{"↓"}</button>
+            </span>
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Serde-derived Rust structs for the document types
+
+use serde_json::Value;
+
+use crate::{export::{pascal_case, required_names}, is_identifier};
+
+/// Strict and reserved keywords that can be used as raw identifiers
+const RUST_KEYWORDS: [&str; 48] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn", "else", "enum", "extern", "false", "final", "fn",
+    "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const HEADER: &str = r#"// Generated by Data Contract Creator
+
+use serde::{Deserialize, Serialize};
+
+/// A 32-byte Dash Platform identifier, as the base58 string it is in document JSON
+pub type Identifier = String;
+"#;
+
+/// Generates one struct per document type holding the document's data fields
+pub fn generate(documents: &Value) -> String {
+    let mut structs = vec![HEADER.to_string()];
+    let mut names = vec![String::from("Identifier")];
+    if let Some(documents) = documents.as_object() {
+        for (name, schema) in documents {
+            let doc = format!("Data fields of the `{}` document type", name);
+            write_struct(&pascal_case(name), schema, Some(doc), &mut structs, &mut names);
+        }
+    }
+    structs.join("\n")
+}
+
+/// Writes the struct for an object schema after the structs of its nested objects. Returns the struct's
+/// name, which gets a numeric suffix when another struct already has it, as `a`+`bC` and `aB`+`c` would.
+fn write_struct(name: &str, schema: &Value, doc: Option<String>, structs: &mut Vec<String>, names: &mut Vec<String>) -> String {
+    let name = unique_type_name(name, names);
+    let required = required_names(schema);
+    let mut out = String::new();
+    for line in doc.iter().chain(description(schema).iter()) {
+        out.push_str(&format!("/// {}\n", line));
+    }
+    out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    out.push_str(&format!("pub struct {} {{\n", name));
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        let mut used_names: Vec<String> = Vec::new();
+        for (prop_name, property) in properties {
+            let field_type = rust_type(property, &format!("{}{}", name, pascal_case(prop_name)), structs, names);
+            let field_name = unique_field_name(field_name(prop_name), &used_names);
+            used_names.push(field_name.clone());
+            if let Some(text) = description(property) {
+                out.push_str(&format!("    /// {}\n", text));
+            }
+            if field_name.trim_start_matches("r#") != prop_name.as_str() {
+                out.push_str(&format!("    #[serde(rename = {})]\n", Value::String(prop_name.clone())));
+            }
+            if required.contains(prop_name) {
+                out.push_str(&format!("    pub {}: {},\n", field_name, field_type));
+            } else {
+                out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                out.push_str(&format!("    pub {}: Option<{}>,\n", field_name, field_type));
+            }
+        }
+    }
+    out.push_str("}\n");
+    structs.push(out);
+    name
+}
+
+/// Adds a numeric suffix to a type name that is already taken, and records the result as taken
+fn unique_type_name(name: &str, names: &mut Vec<String>) -> String {
+    let name = if names.iter().any(|taken| taken == name) {
+        (2..).map(|n| format!("{}{}", name, n)).find(|candidate| !names.contains(candidate)).unwrap()
+    } else {
+        name.to_string()
+    };
+    names.push(name.clone());
+    name
+}
+
+/// Rust type for a property schema. Nested objects become structs named after `nested_name`.
+fn rust_type(property: &Value, nested_name: &str, structs: &mut Vec<String>, names: &mut Vec<String>) -> String {
+    match property.get("type").and_then(|t| t.as_str()).unwrap_or_default() {
+        "string" => String::from("String"),
+        "integer" => integer_type(property.get("minimum").and_then(|v| v.as_i64()), property.get("maximum").and_then(|v| v.as_i64())).to_string(),
+        "number" => String::from("f64"),
+        "boolean" => String::from("bool"),
+        "array" => {
+            if is_identifier(property) {
+                String::from("Identifier")
+            } else if property.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+                String::from("Vec<u8>")
+            } else {
+                match property.get("items") {
+                    Some(items) => format!("Vec<{}>", rust_type(items, &format!("{}Item", nested_name), structs, names)),
+                    None => String::from("Vec<serde_json::Value>"),
+                }
+            }
+        }
+        "object" => write_struct(nested_name, property, None, structs, names),
+        _ => String::from("serde_json::Value"),
+    }
+}
+
+/// Picks the narrowest integer type that holds every value between the declared bounds
+fn integer_type(minimum: Option<i64>, maximum: Option<i64>) -> &'static str {
+    match (minimum, maximum) {
+        (Some(min), Some(max)) if min >= 0 => {
+            if max <= u8::MAX as i64 { "u8" } else if max <= u16::MAX as i64 { "u16" } else if max <= u32::MAX as i64 { "u32" } else { "u64" }
+        }
+        (Some(min), None) if min >= 0 => "u64",
+        (Some(min), Some(max)) => {
+            if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+                "i8"
+            } else if min >= i16::MIN as i64 && max <= i16::MAX as i64 {
+                "i16"
+            } else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+                "i32"
+            } else {
+                "i64"
+            }
+        }
+        _ => "i64",
+    }
+}
+
+/// Converts a property name to a snake_case field name, escaping Rust keywords
+fn field_name(name: &str) -> String {
+    let mut field = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !field.ends_with('_') {
+                field.push('_');
+            }
+            field.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            field.push(c);
+        } else if !field.ends_with('_') {
+            field.push('_');
+        }
+    }
+    if field.is_empty() || field.starts_with(|c: char| c.is_ascii_digit()) {
+        field.insert(0, '_');
+    }
+    if ["self", "super", "crate"].contains(&field.as_str()) {
+        // These keywords can't be raw identifiers
+        format!("{}_", field)
+    } else if RUST_KEYWORDS.contains(&field.as_str()) {
+        format!("r#{}", field)
+    } else {
+        field
+    }
+}
+
+/// Adds a numeric suffix when another property of the struct already maps to the same field name,
+/// as `fooBar` and `foo_bar` do
+fn unique_field_name(field: String, used_names: &[String]) -> String {
+    if !used_names.contains(&field) {
+        return field;
+    }
+    let base = field.trim_start_matches("r#");
+    (2..).map(|n| format!("{}_{}", base, n)).find(|candidate| !used_names.contains(candidate)).unwrap()
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema.get("description").and_then(|d| d.as_str()).filter(|d| !d.is_empty()).map(|d| d.replace('\n', " "))
+}
@@ -0,0 +1,549 @@
+//! Generation of example documents from document schemas
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Map, Value};
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, TargetCast};
+
+use crate::{diff::pointer_segment, files::Download, Model, Msg, is_identifier};
+
+/// Number of valid samples generated per document type
+const VALID_SAMPLE_COUNT: usize = 3;
+
+/// Characters used to fill strings that have no pattern
+const FILLER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Longest string, byte array or array generated. A large `maxLength` or `maxItems` is cut down to
+/// this so the page doesn't build huge documents.
+const MAX_GENERATED_LENGTH: usize = 4096;
+
+/// Which kind of samples to generate
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SampleMode {
+    /// Typical documents that satisfy the schema
+    #[default]
+    Valid,
+    /// Documents at the minimum and maximum boundaries plus known-invalid counterexamples
+    EdgeCases,
+}
+
+impl SampleMode {
+    pub const ALL: [SampleMode; 2] = [SampleMode::Valid, SampleMode::EdgeCases];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SampleMode::Valid => "Valid",
+            SampleMode::EdgeCases => "Edge cases",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "Edge cases" => SampleMode::EdgeCases,
+            _ => SampleMode::Valid,
+        }
+    }
+}
+
+/// Which values to pick within the bounds of a schema
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Typical,
+    Min,
+    Max,
+}
+
+/// A generated document
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// What the document demonstrates
+    pub description: String,
+    /// Whether the document is expected to pass validation
+    pub valid: bool,
+    pub document: Value,
+}
+
+/// Generates samples for a document type schema
+pub fn generate_samples(schema: &Value, mode: SampleMode) -> Vec<Sample> {
+    match mode {
+        SampleMode::Valid => (0..VALID_SAMPLE_COUNT)
+            .map(|seed| bounded_sample(format!("Sample {}", seed + 1), schema, Bound::Typical, seed))
+            .filter(|sample| sample.valid)
+            .collect(),
+        SampleMode::EdgeCases => {
+            let mut samples = vec![
+                bounded_sample(String::from("Minimum: only required fields at their lower bounds"), schema, Bound::Min, 0),
+                bounded_sample(String::from("Maximum: all fields at their upper bounds"), schema, Bound::Max, 0),
+            ];
+            let base = sample_value(schema, Bound::Typical, 0, &mut Cut::default());
+            let mut skipped = Vec::new();
+            counterexamples(schema, &base, "", "", &mut samples, &mut skipped);
+            if !skipped.is_empty() {
+                samples[1].description.push_str(&format!(". No counterexample was generated for {}, since its value would be over {} characters or items long", skipped.join(", "), MAX_GENERATED_LENGTH));
+            }
+            samples
+        }
+    }
+}
+
+/// How generating a value had to cut lengths down to `MAX_GENERATED_LENGTH`
+#[derive(Default)]
+struct Cut {
+    /// A length was cut down
+    truncated: bool,
+    /// A length was cut below the schema's minimum, so the value no longer satisfies it
+    below_minimum: bool,
+}
+
+/// Generates a document at the given bound, noting in the description when a length was cut down.
+/// The sample is marked invalid when a minimum length is over `MAX_GENERATED_LENGTH`.
+fn bounded_sample(description: String, schema: &Value, bound: Bound, seed: usize) -> Sample {
+    let mut cut = Cut::default();
+    let document = sample_value(schema, bound, seed, &mut cut);
+    let description = if cut.below_minimum {
+        format!("{} (truncated to {} characters or items, below a minimum, so it doesn't satisfy the schema)", description, MAX_GENERATED_LENGTH)
+    } else if cut.truncated {
+        format!("{} (truncated to {} characters or items)", description, MAX_GENERATED_LENGTH)
+    } else {
+        description
+    };
+    Sample { description, valid: !cut.below_minimum, document }
+}
+
+/// Limits a generated length to `MAX_GENERATED_LENGTH`, noting when that puts it below `min`
+fn capped(len: usize, min: Option<i64>, cut: &mut Cut) -> usize {
+    if len > MAX_GENERATED_LENGTH {
+        cut.truncated = true;
+        if min.map_or(false, |min| min > MAX_GENERATED_LENGTH as i64) {
+            cut.below_minimum = true;
+        }
+        MAX_GENERATED_LENGTH
+    } else {
+        len
+    }
+}
+
+/// Picks a value between a lower and an upper bound
+fn pick(bound: Bound, min: Option<i64>, max: Option<i64>, typical: i64) -> i64 {
+    let value = match bound {
+        Bound::Min => min.unwrap_or(typical.min(max.unwrap_or(typical))),
+        Bound::Max => max.unwrap_or(typical.max(min.unwrap_or(typical))),
+        Bound::Typical => typical,
+    };
+    let value = min.map_or(value, |min| value.max(min));
+    max.map_or(value, |max| value.min(max))
+}
+
+fn keyword_i64(schema: &Value, keyword: &str) -> Option<i64> {
+    schema.get(keyword).and_then(|v| v.as_i64())
+}
+
+/// Generates a value that satisfies a property schema. Records in `cut` when a length was cut down to `MAX_GENERATED_LENGTH`.
+fn sample_value(schema: &Value, bound: Bound, seed: usize, cut: &mut Cut) -> Value {
+    match schema.get("type").and_then(|t| t.as_str()).unwrap_or("object") {
+        "string" => json!(sample_string(schema, bound, seed, cut)),
+        "integer" => json!(pick(bound, keyword_i64(schema, "minimum"), keyword_i64(schema, "maximum"), seed as i64 + 1)),
+        "number" => {
+            let min = schema.get("minimum").and_then(|v| v.as_f64());
+            let max = schema.get("maximum").and_then(|v| v.as_f64());
+            let mut value = match bound {
+                Bound::Min => min.unwrap_or(0.0),
+                Bound::Max => max.unwrap_or(100.5),
+                Bound::Typical => seed as f64 + 1.5,
+            };
+            if let Some(min) = min {
+                value = value.max(min);
+            }
+            if let Some(max) = max {
+                value = value.min(max);
+            }
+            json!(value)
+        }
+        "boolean" => json!(seed % 2 == 0),
+        "array" => {
+            let min = keyword_i64(schema, "minItems");
+            let max = keyword_i64(schema, "maxItems");
+            if schema.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+                let identifier = is_identifier(schema);
+                let len = if identifier { 32 } else { capped(pick(bound, min, max, 32).max(0) as usize, min, cut) };
+                json!(encode_bytes(&vec![seed as u8 + 1; len], identifier))
+            } else {
+                let len = capped(pick(bound, min, max, 1).max(0) as usize, min, cut);
+                let items = schema.get("items").cloned().unwrap_or(json!({"type": "string"}));
+                json!((0..len).map(|i| sample_value(&items, bound, seed + i, cut)).collect::<Vec<_>>())
+            }
+        }
+        _ => {
+            let mut obj = Map::new();
+            let required: Vec<&str> = schema.get("required").and_then(|r| r.as_array()).map(|r| r.iter().filter_map(|v| v.as_str()).collect()).unwrap_or_default();
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                for (name, property) in properties {
+                    if bound == Bound::Min && !required.contains(&name.as_str()) {
+                        continue;
+                    }
+                    obj.insert(name.clone(), sample_value(property, bound, seed, cut));
+                }
+            }
+            Value::Object(obj)
+        }
+    }
+}
+
+/// Encodes bytes the way they appear in a JSON document: identifiers as base58, other byte arrays as base64
+fn encode_bytes(bytes: &[u8], identifier: bool) -> String {
+    if identifier {
+        bs58::encode(bytes).into_string()
+    } else {
+        STANDARD.encode(bytes)
+    }
+}
+
+fn sample_string(schema: &Value, bound: Bound, seed: usize, cut: &mut Cut) -> String {
+    let min = keyword_i64(schema, "minLength");
+    let max = keyword_i64(schema, "maxLength");
+    let len = capped(pick(bound, min, max, 8).max(0) as usize, min, cut);
+    let max_len = max.map_or(MAX_GENERATED_LENGTH, |max| (max.max(0) as usize).min(MAX_GENERATED_LENGTH));
+    if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
+        if let Some(sample) = pattern_sample(pattern, len, max_len, seed) {
+            return sample;
+        }
+    }
+    match schema.get("format").and_then(|f| f.as_str()) {
+        Some("date-time") => return String::from("2023-01-01T00:00:00Z"),
+        Some("date") => return String::from("2023-01-01"),
+        Some("time") => return String::from("00:00:00Z"),
+        Some("email") => return format!("user{}@example.com", seed + 1),
+        Some("uri") | Some("url") => return format!("https://example.com/{}", seed + 1),
+        Some("hostname") => return String::from("example.com"),
+        Some("ipv4") => return String::from("192.0.2.1"),
+        Some("ipv6") => return String::from("2001:db8::1"),
+        Some("uuid") => return String::from("123e4567-e89b-12d3-a456-426614174000"),
+        _ => {}
+    }
+    (0..len).map(|i| FILLER[(seed + i) % FILLER.len()] as char).collect()
+}
+
+/// A regex atom with the characters it matches and how often it repeats
+struct PatternToken {
+    chars: Vec<char>,
+    min: usize,
+    max: usize,
+}
+
+/// Generates a string of about `len` characters matching a simple regex pattern made of literals,
+/// character classes and quantifiers. Returns None for patterns using groups or alternation, and
+/// for patterns that need more than `max_len` characters.
+fn pattern_sample(pattern: &str, len: usize, max_len: usize, seed: usize) -> Option<String> {
+    let tokens = parse_pattern(pattern)?;
+    let mut counts: Vec<usize> = tokens.iter().map(|t| t.min).collect();
+    let required = counts.iter().try_fold(0usize, |sum, count| sum.checked_add(*count))?;
+    if required > max_len {
+        return None;
+    }
+    let mut remaining = len.min(max_len).saturating_sub(required);
+    for (count, token) in counts.iter_mut().zip(tokens.iter()) {
+        let extra = remaining.min(token.max - token.min);
+        *count += extra;
+        remaining -= extra;
+    }
+    let mut sample = String::new();
+    for (count, token) in counts.iter().zip(tokens.iter()) {
+        for i in 0..*count {
+            sample.push(token.chars[(seed + i) % token.chars.len()]);
+        }
+    }
+    Some(sample)
+}
+
+fn parse_pattern(pattern: &str) -> Option<Vec<PatternToken>> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let atom = match chars[i] {
+            '^' | '$' => {
+                i += 1;
+                continue;
+            }
+            '(' | ')' | '|' => return None,
+            '[' => {
+                let end = chars[i..].iter().position(|c| *c == ']')? + i;
+                let class = parse_class(&chars[i + 1..end])?;
+                i = end + 1;
+                class
+            }
+            '\\' => {
+                let class = escape_class(*chars.get(i + 1)?);
+                i += 2;
+                class
+            }
+            '.' => {
+                i += 1;
+                FILLER.iter().map(|c| *c as char).collect()
+            }
+            c => {
+                i += 1;
+                vec![c]
+            }
+        };
+        let (min, max) = match chars.get(i) {
+            Some('?') => { i += 1; (0, 1) }
+            Some('*') => { i += 1; (0, usize::MAX) }
+            Some('+') => { i += 1; (1, usize::MAX) }
+            Some('{') => {
+                let end = chars[i..].iter().position(|c| *c == '}')? + i;
+                let range: String = chars[i + 1..end].iter().collect();
+                i = end + 1;
+                match range.split_once(',') {
+                    Some((min, "")) => (min.trim().parse().ok()?, usize::MAX),
+                    Some((min, max)) => {
+                        let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+                        if max < min {
+                            return None;
+                        }
+                        (min, max)
+                    }
+                    None => {
+                        let n = range.trim().parse().ok()?;
+                        (n, n)
+                    }
+                }
+            }
+            _ => (1, 1),
+        };
+        if atom.is_empty() {
+            return None;
+        }
+        tokens.push(PatternToken { chars: atom, min, max });
+    }
+    Some(tokens)
+}
+
+fn escape_class(c: char) -> Vec<char> {
+    match c {
+        'd' => ('0'..='9').collect(),
+        'w' => ('a'..='z').chain('0'..='9').chain(std::iter::once('_')).collect(),
+        's' => vec![' '],
+        c => vec![c],
+    }
+}
+
+/// Parses the inside of a character class such as `a-zA-Z0-9_-`
+fn parse_class(class: &[char]) -> Option<Vec<char>> {
+    if class.first() == Some(&'^') {
+        return None;
+    }
+    let mut chars = Vec::new();
+    let mut i = 0;
+    while i < class.len() {
+        if class[i] == '\\' {
+            chars.extend(escape_class(*class.get(i + 1)?));
+            i += 2;
+        } else if i + 2 < class.len() && class[i + 1] == '-' {
+            chars.extend(class[i]..=class[i + 2]);
+            i += 3;
+        } else {
+            chars.push(class[i]);
+            i += 1;
+        }
+    }
+    // Prefer letters so samples read naturally, but keep every character available
+    chars.sort_by_key(|c| !c.is_ascii_lowercase());
+    Some(chars)
+}
+
+/// Adds a copy of `base` with the value at `pointer` replaced, or removed when `value` is None
+fn push_invalid(samples: &mut Vec<Sample>, base: &Value, description: String, pointer: &str, value: Option<Value>) {
+    let mut document = base.clone();
+    let (parent, key) = pointer.rsplit_once('/').unwrap_or(("", pointer));
+    if let Some(parent) = document.pointer_mut(parent).and_then(|p| p.as_object_mut()) {
+        let key = key.replace("~1", "/").replace("~0", "~");
+        match value {
+            Some(value) => parent.insert(key, value),
+            None => parent.remove(&key),
+        };
+        samples.push(Sample { description, valid: false, document });
+    }
+}
+
+/// Adds documents that each break one constraint of the object schema at `pointer`. Values that
+/// would be longer than `MAX_GENERATED_LENGTH` are not generated and their cases go into `skipped`.
+fn counterexamples(schema: &Value, base: &Value, pointer: &str, name: &str, samples: &mut Vec<Sample>, skipped: &mut Vec<String>) {
+    let prefix = if name.is_empty() { String::new() } else { format!("{}.", name) };
+
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for field in required.iter().filter_map(|f| f.as_str()) {
+            push_invalid(samples, base, format!("Missing required field \"{}{}\"", prefix, field), &format!("{}/{}", pointer, pointer_segment(field)), None);
+        }
+    }
+    if schema.get("additionalProperties") == Some(&json!(false)) {
+        push_invalid(samples, base, format!("Unknown property \"{}unexpectedProperty\"", prefix), &format!("{}/unexpectedProperty", pointer), Some(json!(true)));
+    }
+
+    let properties = match schema.get("properties").and_then(|p| p.as_object()) {
+        Some(properties) => properties,
+        None => return,
+    };
+    for (prop_name, property) in properties {
+        let prop_pointer = format!("{}/{}", pointer, pointer_segment(prop_name));
+        let display_name = format!("{}{}", prefix, prop_name);
+        let data_type = property.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+        let wrong_type = if data_type == "string" { json!(42) } else { json!("wrong type") };
+        push_invalid(samples, base, format!("Wrong type for \"{}\"", display_name), &prop_pointer, Some(wrong_type));
+
+        match data_type {
+            "string" => {
+                if let Some(min) = keyword_i64(property, "minLength").filter(|min| *min > 0) {
+                    let mut cut = Cut::default();
+                    let len = capped(min as usize - 1, None, &mut cut);
+                    let description = format!("\"{}\" shorter than minLength {}{}", display_name, min, if cut.truncated { format!(" (truncated to {} characters)", len) } else { String::new() });
+                    push_invalid(samples, base, description, &prop_pointer, Some(json!("a".repeat(len))));
+                }
+                if let Some(max) = keyword_i64(property, "maxLength").filter(|max| *max >= 0) {
+                    if max as usize >= MAX_GENERATED_LENGTH {
+                        skipped.push(format!("\"{}\" longer than maxLength {}", display_name, max));
+                    } else {
+                        push_invalid(samples, base, format!("\"{}\" longer than maxLength {}", display_name, max), &prop_pointer, Some(json!("a".repeat(max as usize + 1))));
+                    }
+                }
+            }
+            "integer" | "number" => {
+                if let Some(min) = keyword_i64(property, "minimum") {
+                    push_invalid(samples, base, format!("\"{}\" below minimum {}", display_name, min), &prop_pointer, Some(json!(min - 1)));
+                }
+                if let Some(max) = keyword_i64(property, "maximum") {
+                    push_invalid(samples, base, format!("\"{}\" above maximum {}", display_name, max), &prop_pointer, Some(json!(max + 1)));
+                }
+            }
+            "array" => {
+                let is_bytes = property.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false);
+                let identifier = is_identifier(property);
+                let items = |len: usize| if is_bytes { json!(encode_bytes(&vec![1; len], identifier)) } else { json!(vec![sample_value(property.get("items").unwrap_or(&json!({"type": "string"})), Bound::Typical, 0, &mut Cut::default()); len]) };
+                if let Some(min) = keyword_i64(property, "minItems").filter(|min| *min > 0) {
+                    let mut cut = Cut::default();
+                    let len = capped(min as usize - 1, None, &mut cut);
+                    let description = format!("\"{}\" has fewer than minItems {}{}", display_name, min, if cut.truncated { format!(" (truncated to {} items)", len) } else { String::new() });
+                    push_invalid(samples, base, description, &prop_pointer, Some(items(len)));
+                }
+                if let Some(max) = keyword_i64(property, "maxItems").filter(|max| *max >= 0) {
+                    if max as usize >= MAX_GENERATED_LENGTH {
+                        skipped.push(format!("\"{}\" having more than maxItems {}", display_name, max));
+                    } else {
+                        push_invalid(samples, base, format!("\"{}\" has more than maxItems {}", display_name, max), &prop_pointer, Some(items(max as usize + 1)));
+                    }
+                }
+            }
+            "object" => counterexamples(property, base, &prop_pointer, &display_name, samples, skipped),
+            _ => {}
+        }
+    }
+}
+
+impl Model {
+    pub fn generate_sample_documents(&mut self) {
+        self.json_object = self.generate_json_object();
+        let documents = self.documents_json();
+        self.generated_samples = documents
+            .as_object()
+            .map(|documents| documents.iter().map(|(name, schema)| (name.clone(), generate_samples(schema, self.sample_mode))).collect())
+            .unwrap_or_default();
+    }
+
+    /// Copies the generated documents of the selected document type into the document validation input as JSONL and validates them
+    pub fn use_generated_samples(&mut self) {
+        let document_type = self.selected_sample_document_type();
+        if let Some((_, samples)) = self.generated_samples.iter().find(|(name, _)| *name == document_type) {
+            self.sample_documents_input = samples.iter().map(|sample| sample.document.to_string()).collect::<Vec<_>>().join("\n");
+            self.validate_sample_documents();
+        }
+    }
+
+    pub fn generated_samples_json(&self) -> String {
+        let mut output = Map::new();
+        for (name, samples) in &self.generated_samples {
+            let samples: Vec<Value> = match self.sample_mode {
+                SampleMode::Valid => samples.iter().map(|sample| sample.document.clone()).collect(),
+                SampleMode::EdgeCases => samples.iter().map(|sample| json!({
+                    "case": sample.description,
+                    "expectValid": sample.valid,
+                    "document": sample.document,
+                })).collect(),
+            };
+            output.insert(name.clone(), json!(samples));
+        }
+        serde_json::to_string_pretty(&output).unwrap()
+    }
+
+    pub fn view_generated_samples(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <p class="output-container">
+                <h2>{"Generate documents"}</h2>
+                <h3>{"Mode"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| Msg::UpdateSampleMode(SampleMode::from_label(e.target_dyn_into::<HtmlSelectElement>().unwrap().value().as_str())))}>
+                    {for SampleMode::ALL.iter().map(|mode| html! {
+                        <option value={mode.label()} selected={*mode == self.sample_mode}>{mode.label()}</option>
+                    })}
+                </select>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::GenerateSampleDocuments)}>{"Generate documents"}</button></div>
+                {
+                    if !self.generated_samples.is_empty() {
+                        html! {
+                            <>
+                            <pre>
+                            <textarea class="textarea" readonly=true value={self.generated_samples_json()}></textarea>
+                            </pre>
+                            {for self.generated_samples.iter().filter(|(_, samples)| samples.is_empty()).map(|(name, _)| html! {
+                                <p class="error-text">{format!("No valid \"{}\" document was generated, since a minimum length is over {} characters or items", name, MAX_GENERATED_LENGTH)}</p>
+                            })}
+                            <div>
+                                <button class="button" onclick={ctx.link().callback(|_| Msg::UseGeneratedSamples)}>{"Validate these documents"}</button>
+                                <button class="button2" onclick={ctx.link().callback(|_| Msg::Download(Download::Samples))}>{"Download"}</button>
+                            </div>
+                            </>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </p>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_sample_fills_optional_counts_up_to_the_length() {
+        assert_eq!(pattern_sample(r"^[a-z]{3}-\d{2,4}$", 8, MAX_GENERATED_LENGTH, 0).as_deref(), Some("abc-0123"));
+        assert_eq!(pattern_sample(r"^[a-z]{3}-\d{2,4}$", 0, MAX_GENERATED_LENGTH, 1).as_deref(), Some("bcd-12"));
+        assert_eq!(pattern_sample("^[a-z]+$", 5, MAX_GENERATED_LENGTH, 0).as_deref(), Some("abcde"));
+        assert_eq!(pattern_sample("^[a-z]+$", 5, 3, 0).as_deref(), Some("abc"));
+        assert_eq!(pattern_sample(r"^\w?x*$", 0, MAX_GENERATED_LENGTH, 0).as_deref(), Some(""));
+    }
+
+    #[test]
+    fn pattern_sample_rejects_unsupported_and_impossible_patterns() {
+        assert_eq!(pattern_sample("^(a|b)$", 1, MAX_GENERATED_LENGTH, 0), None);
+        assert_eq!(pattern_sample("^[^a]$", 1, MAX_GENERATED_LENGTH, 0), None);
+        assert_eq!(pattern_sample("^a{3,1}$", 2, MAX_GENERATED_LENGTH, 0), None);
+        assert_eq!(pattern_sample("^a{1000000}$", 8, MAX_GENERATED_LENGTH, 0), None);
+        assert_eq!(pattern_sample("^a{2,}b{3}$", 4, 4, 0), None);
+        assert_eq!(pattern_sample("^a{2,}b{3}$", 4, 5, 0).as_deref(), Some("aabbb"));
+    }
+
+    #[test]
+    fn samples_capped_below_a_minimum_are_not_valid() {
+        let schema = json!({"type": "object", "properties": {"text": {"type": "string", "minLength": 5000}}, "required": ["text"]});
+        assert!(generate_samples(&schema, SampleMode::Valid).is_empty());
+        let minimum = &generate_samples(&schema, SampleMode::EdgeCases)[0];
+        assert!(!minimum.valid);
+        assert_eq!(minimum.document["text"].as_str().unwrap().len(), MAX_GENERATED_LENGTH);
+
+        let schema = json!({"type": "object", "properties": {"text": {"type": "string", "maxLength": 5000}}});
+        let maximum = &generate_samples(&schema, SampleMode::EdgeCases)[1];
+        assert!(maximum.valid);
+        assert!(maximum.description.contains("(truncated to 4096 characters or items)"));
+    }
+}
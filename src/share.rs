@@ -0,0 +1,89 @@
+//! Share links that carry the editor state in the URL fragment
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use yew::{html, Html};
+
+use crate::{diagram::Reference, DocumentType, Model, Msg};
+
+/// Prefix of the URL fragment holding the shared state
+const FRAGMENT_PREFIX: &str = "#state=";
+
+/// Upper bound for the decompressed state, so a crafted link can't exhaust memory
+const MAX_STATE_BYTES: usize = 4 * 1024 * 1024;
+
+/// The editor state a share link carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedState {
+    pub document_types: Vec<DocumentType>,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+}
+
+/// Serializes the state to JSON, deflates it and encodes it as URL-safe base64
+pub fn encode_state(state: &SharedState) -> Result<String, String> {
+    let json = serde_json::to_vec(state).map_err(|e| format!("Could not serialize the editor state: {}", e))?;
+    let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+    Ok(URL_SAFE_NO_PAD.encode(compressed))
+}
+
+/// Reverses `encode_state`
+pub fn decode_state(encoded: &str) -> Result<SharedState, String> {
+    let compressed = URL_SAFE_NO_PAD.decode(encoded.trim()).map_err(|e| format!("The share link is damaged: {}", e))?;
+    let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_STATE_BYTES)
+        .map_err(|e| format!("The share link is damaged: {:?}", e.status))?;
+    serde_json::from_slice(&json).map_err(|e| format!("The share link holds an unknown editor state: {}", e))
+}
+
+/// Reads the shared state from the current URL, if there is one
+pub fn state_from_location() -> Option<Result<SharedState, String>> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    hash.strip_prefix(FRAGMENT_PREFIX).map(decode_state)
+}
+
+/// Removes the shared state from the URL once it has been restored
+pub fn clear_location_state() {
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_hash("");
+    }
+}
+
+impl Model {
+    /// Shows a link to this page with the editor state in the URL fragment
+    pub fn share(&mut self) {
+        let state = SharedState { document_types: self.document_types.clone(), references: self.references.clone() };
+        let encoded = match encode_state(&state) {
+            Ok(encoded) => encoded,
+            Err(message) => {
+                self.share_messages = vec![message];
+                return;
+            }
+        };
+        let location = match web_sys::window() {
+            Some(window) => window.location(),
+            None => return,
+        };
+        let origin = location.origin().unwrap_or_default();
+        let pathname = location.pathname().unwrap_or_default();
+        self.share_url = format!("{}{}{}{}", origin, pathname, FRAGMENT_PREFIX, encoded);
+        self.share_messages = vec![];
+    }
+
+    pub fn view_share(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <>
+            <button class="button2" onclick={ctx.link().callback(|_| Msg::Share)}>{"Share"}</button>
+            {
+                if self.share_url.is_empty() {
+                    html! {}
+                } else {
+                    html! {<input type="text" readonly=true value={self.share_url.clone()} />}
+                }
+            }
+            <ul class="error-text">
+                { for self.share_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+            </ul>
+            </>
+        }
+    }
+}
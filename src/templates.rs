@@ -0,0 +1,259 @@
+//! Gallery of starter contracts, built in and saved by the user
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{workspace, Model, Msg};
+
+/// Key of the user's saved templates
+const USER_TEMPLATES_KEY: &str = "data-contract-creator.templates";
+
+/// A built-in starter contract
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Document schemas as JSON
+    pub documents: &'static str,
+}
+
+/// A template saved by the user from one of their contracts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserTemplate {
+    pub name: String,
+    pub documents: Value,
+}
+
+pub const TEMPLATES: [Template; 5] = [
+    Template {
+        name: "Name service",
+        description: "DPNS-style domain names registered through a salted preorder",
+        documents: r#"{
+            "domain": {
+                "type": "object",
+                "properties": {
+                    "label": {"type": "string", "pattern": "^[a-zA-Z0-9][a-zA-Z0-9-]{0,61}[a-zA-Z0-9]$", "minLength": 3, "maxLength": 63, "description": "Domain label as entered by the owner"},
+                    "normalizedLabel": {"type": "string", "pattern": "^[a-hj-km-np-z0-9][a-hj-km-np-z0-9-]{0,61}[a-hj-km-np-z0-9]$", "maxLength": 63, "description": "Lowercase label with homographs replaced, used for uniqueness"},
+                    "normalizedParentDomainName": {"type": "string", "maxLength": 63, "description": "Normalized name of the parent domain"},
+                    "preorderSalt": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "description": "Salt used in the preorder"},
+                    "records": {
+                        "type": "object",
+                        "properties": {
+                            "identity": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "contentMediaType": "application/x.dash.dpp.identifier", "description": "Identity the name resolves to"}
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                "indices": [
+                    {"name": "parentNameAndLabel", "properties": [{"normalizedParentDomainName": "asc"}, {"normalizedLabel": "asc"}], "unique": true}
+                ],
+                "required": ["label", "normalizedLabel", "normalizedParentDomainName", "preorderSalt", "records"],
+                "additionalProperties": false
+            },
+            "preorder": {
+                "type": "object",
+                "properties": {
+                    "saltedDomainHash": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "description": "Double SHA-256 of the salt and the full domain name"}
+                },
+                "indices": [
+                    {"name": "saltedHash", "properties": [{"saltedDomainHash": "asc"}], "unique": true}
+                ],
+                "required": ["saltedDomainHash"],
+                "additionalProperties": false
+            }
+        }"#,
+    },
+    Template {
+        name: "Social profile and contacts",
+        description: "DashPay-style profile and encrypted contact request",
+        documents: r#"{
+            "profile": {
+                "type": "object",
+                "properties": {
+                    "displayName": {"type": "string", "maxLength": 25},
+                    "publicMessage": {"type": "string", "maxLength": 140},
+                    "avatarUrl": {"type": "string", "format": "uri", "maxLength": 2048}
+                },
+                "indices": [
+                    {"name": "ownerId", "properties": [{"$ownerId": "asc"}], "unique": true}
+                ],
+                "additionalProperties": false
+            },
+            "contactRequest": {
+                "type": "object",
+                "properties": {
+                    "toUserId": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "contentMediaType": "application/x.dash.dpp.identifier", "description": "Identity the request is sent to"},
+                    "encryptedPublicKey": {"type": "array", "byteArray": true, "minItems": 96, "maxItems": 96, "description": "Extended public key encrypted for the recipient"},
+                    "senderKeyIndex": {"type": "integer", "description": "Key of the sender used for encryption"},
+                    "recipientKeyIndex": {"type": "integer", "description": "Key of the recipient used for encryption"},
+                    "accountReference": {"type": "integer", "description": "Account the request belongs to"}
+                },
+                "indices": [
+                    {"name": "ownerIdUserIdAndAccountRef", "properties": [{"$ownerId": "asc"}, {"toUserId": "asc"}, {"accountReference": "asc"}], "unique": true},
+                    {"name": "userId", "properties": [{"toUserId": "asc"}]}
+                ],
+                "required": ["toUserId", "encryptedPublicKey", "senderKeyIndex", "recipientKeyIndex", "accountReference"],
+                "additionalProperties": false
+            }
+        }"#,
+    },
+    Template {
+        name: "Social feed",
+        description: "Short posts with replies and likes",
+        documents: r#"{
+            "post": {
+                "type": "object",
+                "properties": {
+                    "message": {"type": "string", "minLength": 1, "maxLength": 280},
+                    "replyToPostId": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "contentMediaType": "application/x.dash.dpp.identifier", "description": "Post this one replies to"}
+                },
+                "indices": [
+                    {"name": "ownerId", "properties": [{"$ownerId": "asc"}]}
+                ],
+                "required": ["message"],
+                "additionalProperties": false
+            },
+            "like": {
+                "type": "object",
+                "properties": {
+                    "postId": {"type": "array", "byteArray": true, "minItems": 32, "maxItems": 32, "contentMediaType": "application/x.dash.dpp.identifier"}
+                },
+                "indices": [
+                    {"name": "ownerIdAndPostId", "properties": [{"$ownerId": "asc"}, {"postId": "asc"}], "unique": true},
+                    {"name": "postId", "properties": [{"postId": "asc"}]}
+                ],
+                "required": ["postId"],
+                "additionalProperties": false
+            }
+        }"#,
+    },
+    Template {
+        name: "Key-value store",
+        description: "One value per key for each owner",
+        documents: r#"{
+            "entry": {
+                "type": "object",
+                "properties": {
+                    "key": {"type": "string", "minLength": 1, "maxLength": 63},
+                    "value": {"type": "string", "maxLength": 1024}
+                },
+                "indices": [
+                    {"name": "ownerIdAndKey", "properties": [{"$ownerId": "asc"}, {"key": "asc"}], "unique": true}
+                ],
+                "required": ["key", "value"],
+                "additionalProperties": false
+            }
+        }"#,
+    },
+    Template {
+        name: "Collectible",
+        description: "NFT-like cards with a name, image, rarity and attributes",
+        documents: r#"{
+            "card": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "minLength": 1, "maxLength": 63},
+                    "description": {"type": "string", "maxLength": 256},
+                    "imageUrl": {"type": "string", "format": "uri", "maxLength": 2048},
+                    "rarity": {"type": "integer", "minimum": 1, "maximum": 5},
+                    "attributes": {
+                        "type": "object",
+                        "properties": {
+                            "strength": {"type": "integer", "minimum": 1, "maximum": 100},
+                            "speed": {"type": "integer", "minimum": 1, "maximum": 100}
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                "indices": [
+                    {"name": "name", "properties": [{"name": "asc"}]},
+                    {"name": "ownerIdAndRarity", "properties": [{"$ownerId": "asc"}, {"rarity": "asc"}]}
+                ],
+                "required": ["name", "rarity"],
+                "additionalProperties": false
+            }
+        }"#,
+    },
+];
+
+pub fn load_user_templates() -> Vec<UserTemplate> {
+    workspace::read(USER_TEMPLATES_KEY).unwrap_or_default()
+}
+
+impl Model {
+    /// Opens the document schemas of a template in a new project
+    fn open_template(&mut self, name: String, documents: String) {
+        self.autosave();
+        self.create_project(name, vec![]);
+        self.imported_json = documents;
+        self.template_messages = match self.parse_imported_json() {
+            Ok(dropped) => dropped,
+            Err(errors) => errors,
+        };
+    }
+
+    pub fn use_template(&mut self, index: usize) {
+        if let Some(template) = TEMPLATES.get(index) {
+            self.open_template(template.name.to_string(), template.documents.to_string());
+        }
+    }
+
+    pub fn use_user_template(&mut self, index: usize) {
+        if let Some(template) = self.user_templates.get(index) {
+            let (name, documents) = (template.name.clone(), template.documents.to_string());
+            self.open_template(name, documents);
+        }
+    }
+
+    /// Saves the current document types as a template, replacing a saved template of the same name
+    pub fn save_template(&mut self) {
+        let name = self.template_name.trim().to_string();
+        if name.is_empty() {
+            self.template_messages = vec![String::from("Enter a name for the template")];
+            return;
+        }
+        self.json_object = self.generate_json_object();
+        let template = UserTemplate { name: name.clone(), documents: self.documents_json() };
+        match self.user_templates.iter_mut().find(|t| t.name == name) {
+            Some(existing) => *existing = template,
+            None => self.user_templates.push(template),
+        }
+        self.template_name = String::new();
+        self.template_messages = workspace::write(USER_TEMPLATES_KEY, &self.user_templates).err().into_iter().collect();
+    }
+
+    pub fn delete_user_template(&mut self, index: usize) {
+        if index < self.user_templates.len() {
+            self.user_templates.remove(index);
+            self.template_messages = workspace::write(USER_TEMPLATES_KEY, &self.user_templates).err().into_iter().collect();
+        }
+    }
+
+    pub fn view_templates(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="input-fields">
+                <h2>{"Templates"}</h2>
+                <ul class="template-list">
+                    {for TEMPLATES.iter().enumerate().map(|(i, template)| html! {
+                        <li>
+                            <b>{template.name}</b>{" — "}{template.description}{" "}
+                            <button class="button2" onclick={ctx.link().callback(move |_| Msg::UseTemplate(i))}>{"Use"}</button>
+                        </li>
+                    })}
+                    {for self.user_templates.iter().enumerate().map(|(i, template)| html! {
+                        <li>
+                            <b>{template.name.clone()}</b>{" — saved template "}
+                            <button class="button2" onclick={ctx.link().callback(move |_| Msg::UseUserTemplate(i))}>{"Use"}</button>
+                            <button class="button2" onclick={ctx.link().callback(move |_| Msg::DeleteUserTemplate(i))}>{"Delete"}</button>
+                        </li>
+                    })}
+                </ul>
+                <input type="text" placeholder="Template name" value={self.template_name.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateTemplateName(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <div><button class="button2" onclick={ctx.link().callback(|_| Msg::SaveTemplate)}>{"Save as template"}</button></div>
+                <ul class="error-text">
+                    { for self.template_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                </ul>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Offline building and signing of data contract state transitions
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dpp::{
+    dashcore::{secp256k1::{PublicKey, Secp256k1, SecretKey}, PrivateKey},
+    data_contract::{CreatedDataContract, DataContract},
+    identity::{KeyID, KeyType},
+    platform_value::string_encoding::Encoding,
+    prelude::Identifier,
+    state_transition::{StateTransitionConvert, StateTransitionIdentitySigned, StateTransitionLike},
+    BlsModule, ProtocolError, PublicKeyValidationError,
+};
+use serde_json::{json, Map, Value};
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, InputEvent, TargetCast};
+
+use crate::{data_contract_factory, diff, files::Download, Model, Msg};
+
+/// Output encodings for a serialized state transition
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TransitionEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl TransitionEncoding {
+    pub const ALL: [TransitionEncoding; 2] = [TransitionEncoding::Hex, TransitionEncoding::Base64];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransitionEncoding::Hex => "Hex",
+            TransitionEncoding::Base64 => "Base64",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "Base64" => TransitionEncoding::Base64,
+            _ => TransitionEncoding::Hex,
+        }
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            TransitionEncoding::Hex => hex::encode(bytes),
+            TransitionEncoding::Base64 => STANDARD.encode(bytes),
+        }
+    }
+}
+
+/// A signed and serialized state transition
+pub struct SignedTransition {
+    /// The serialized transition, signature included
+    pub bytes: Vec<u8>,
+    /// Whether the signature was verified against the public key derived from the private key
+    pub verified: bool,
+}
+
+/// Only ECDSA keys can be pasted into the editor, so BLS operations are never needed
+struct NoBlsModule;
+
+impl BlsModule for NoBlsModule {
+    fn validate_public_key(&self, _pk: &[u8]) -> Result<(), PublicKeyValidationError> {
+        Err(PublicKeyValidationError::new("BLS keys are not supported"))
+    }
+
+    fn verify_signature(&self, _signature: &[u8], _data: &[u8], _public_key: &[u8]) -> Result<bool, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+
+    fn private_key_to_public_key(&self, _private_key: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+
+    fn sign(&self, _data: &[u8], _private_key: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+}
+
+/// Parses a private key given either as WIF or as 64 hex characters
+pub fn parse_private_key(input: &str) -> Result<[u8; 32], String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(String::from("Private key is required"));
+    }
+    let bytes = if input.len() == 64 && input.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(input).map_err(|e| format!("Invalid hex private key: {}", e))?
+    } else {
+        PrivateKey::from_wif(input).map_err(|e| format!("Invalid WIF private key: {}", e))?.to_bytes()
+    };
+    bytes.try_into().map_err(|_| String::from("Private key must be 32 bytes"))
+}
+
+/// Derives the compressed secp256k1 public key that an identity would register for the private key
+fn public_key_for(private_key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(private_key).map_err(|e| format!("Invalid private key: {}", e))?;
+    Ok(PublicKey::from_secret_key(&secp, &secret_key).serialize().to_vec())
+}
+
+/// Parses a base58 identity ID
+pub fn parse_identity_id(input: &str) -> Result<Identifier, String> {
+    Identifier::from_string(input.trim(), Encoding::Base58).map_err(|e| format!("Invalid identity ID: {}", e))
+}
+
+/// Signs a state transition with an ECDSA key, checks the signature and serializes the result
+pub fn sign_transition<T>(mut transition: T, private_key: &[u8; 32], key_id: KeyID) -> Result<SignedTransition, String>
+where
+    T: StateTransitionLike + StateTransitionIdentitySigned + StateTransitionConvert,
+{
+    let bls = NoBlsModule;
+    transition.set_signature_public_key_id(key_id);
+    transition
+        .sign_by_private_key(private_key, KeyType::ECDSA_SECP256K1, &bls)
+        .map_err(|e| format!("Signing failed: {}", e))?;
+    let public_key = public_key_for(private_key)?;
+    let verified = transition.verify_by_public_key(&public_key, KeyType::ECDSA_SECP256K1, &bls).is_ok();
+    let bytes = transition.to_buffer(false).map_err(|e| format!("Serialization failed: {}", e))?;
+    Ok(SignedTransition { bytes, verified })
+}
+
+/// Builds a DataContractCreateTransition for the document schemas and signs it offline
+pub fn build_create_transition(documents: Value, identity_id: &str, private_key: &str, key_id: KeyID) -> Result<SignedTransition, String> {
+    let owner_id = parse_identity_id(identity_id)?;
+    let private_key = parse_private_key(private_key)?;
+    let factory = data_contract_factory();
+    let created: CreatedDataContract = factory
+        .create(owner_id, documents.into(), None, None)
+        .map_err(|e| format!("Could not create data contract: {}", e))?;
+    let transition = factory
+        .create_data_contract_create_transition(created)
+        .map_err(|e| format!("Could not create state transition: {}", e))?;
+    sign_transition(transition, &private_key, key_id)
+}
+
+/// A deployed data contract loaded as the base for an update
+pub struct BaseContract {
+    /// The full contract as it was pasted
+    pub contract: Value,
+    /// Key holding the document schemas in the pasted contract
+    documents_key: String,
+    /// The deployed document schemas
+    pub documents: Map<String, Value>,
+    /// The deployed document schemas as the form generates them after loading them, which edits
+    /// are compared with so the round trip itself doesn't count as an edit
+    pub generated: Map<String, Value>,
+    /// What the form couldn't keep from the deployed schemas. An update would remove it, so it
+    /// isn't signed.
+    pub dropped: Vec<String>,
+    /// The deployed contract version
+    pub version: u64,
+}
+
+/// Parses a deployed data contract in its JSON form
+pub fn parse_base_contract(input: &str) -> Result<BaseContract, String> {
+    let contract: Value = serde_json::from_str(input).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let contract_obj = contract.as_object().ok_or_else(|| String::from("The base contract must be a JSON object"))?;
+    let documents_key = ["documents", "documentSchemas"]
+        .into_iter()
+        .find(|key| contract_obj.contains_key(*key))
+        .ok_or_else(|| String::from("The base contract has no \"documents\" field"))?;
+    let documents = contract_obj[documents_key].as_object().cloned().ok_or_else(|| String::from("The base contract documents must be an object"))?;
+    let version = contract_obj.get("version").and_then(|v| v.as_u64()).ok_or_else(|| String::from("The base contract has no \"version\" field"))?;
+    Ok(BaseContract { contract, documents_key: documents_key.to_string(), documents, generated: Map::new(), dropped: vec![], version })
+}
+
+/// Lists the edits that DPP's data contract update rules reject
+pub fn update_violations(base: &BaseContract, documents: &Value) -> Vec<String> {
+    let empty = Map::new();
+    diff::diff_documents(&base.generated, documents.as_object().unwrap_or(&empty))
+        .into_iter()
+        .filter(|change| change.breaking)
+        .map(|change| format!("{}, Path: {}", change.description, change.path))
+        .collect()
+}
+
+/// Builds a DataContractUpdateTransition that replaces the base contract's documents and bumps its version
+pub fn build_update_transition(base: &BaseContract, documents: Value, private_key: &str, key_id: KeyID) -> Result<SignedTransition, String> {
+    let private_key = parse_private_key(private_key)?;
+    let mut contract = base.contract.clone();
+    contract[base.documents_key.as_str()] = documents;
+    contract["version"] = json!(base.version + 1);
+    let data_contract = DataContract::from_json_object(contract).map_err(|e| format!("Could not load updated data contract: {}", e))?;
+    let transition = data_contract_factory()
+        .create_data_contract_update_transition(data_contract)
+        .map_err(|e| format!("Could not create state transition: {}", e))?;
+    sign_transition(transition, &private_key, key_id)
+}
+
+impl Model {
+    pub fn view_transition(&self, ctx: &yew::Context<Self>) -> Html {
+        let encoded = self.transition_encoding.encode(&self.transition_bytes);
+        html! {
+            <p class="output-container">
+                <h2>{"State transition"}</h2>
+                <h3>{"Identity ID"}</h3>
+                <input type="text3" placeholder="Base58 identity ID" value={self.transition_identity_id.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateTransitionIdentityId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <h3>{"Key ID"}</h3>
+                <input type="number" min="0" value={self.transition_key_id.to_string()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateTransitionKeyId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value_as_number() as u32))} />
+                <h3>{"Private key"}</h3>
+                <input type="password" placeholder="WIF or hex" value={self.transition_private_key.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateTransitionPrivateKey(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <h3>{"Encoding"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| Msg::UpdateTransitionEncoding(TransitionEncoding::from_label(e.target_dyn_into::<HtmlSelectElement>().unwrap().value().as_str())))}>
+                    {for TransitionEncoding::ALL.iter().map(|encoding| html! {
+                        <option value={encoding.label()} selected={*encoding == self.transition_encoding}>{encoding.label()}</option>
+                    })}
+                </select>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::BuildCreateTransition)}>{"Build and sign"}</button></div>
+                {self.view_update_transition(ctx)}
+                {
+                    if !self.transition_messages.is_empty() {
+                        html! {
+                            <ul class="error-text">
+                                { for self.transition_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                            </ul>
+                        }
+                    } else if !self.transition_bytes.is_empty() && self.transition_verified {
+                        html! {<p class="passed-text">{"Signature verified ✓"}</p>}
+                    } else if !self.transition_bytes.is_empty() {
+                        html! {<p class="error-text">{"Signature could not be verified"}</p>}
+                    } else {
+                        html! {}
+                    }
+                }
+                <pre>
+                <textarea class="textarea" readonly=true placeholder="Signed transition" value={encoded}></textarea>
+                </pre>
+                <p><b>{format!("Size: {} bytes", self.transition_bytes.len())}</b></p>
+                <div><button class="button2" onclick={ctx.link().callback(|_| Msg::Download(Download::Transition))}>{"Download"}</button></div>
+            </p>
+        }
+    }
+
+    fn view_update_transition(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <>
+            <h3>{"Update a deployed contract"}</h3>
+            <textarea class="textarea" placeholder="Paste the deployed contract here" value={self.base_contract_json.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateBaseContractJson(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+            <div><button class="button" onclick={ctx.link().callback(|_| Msg::LoadBaseContract)}>{"Load base"}</button></div>
+            {
+                if let Some(base) = &self.base_contract {
+                    html! {
+                        <>
+                        <p>{format!("Version {} will be updated to version {}", base.version, base.version + 1)}</p>
+                        {
+                            if !base.dropped.is_empty() {
+                                html! {
+                                    <>
+                                    <h3>{"Not kept by the form, so the update can't be signed:"}</h3>
+                                    <ul class="error-text">
+                                        { for base.dropped.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                                    </ul>
+                                    </>
+                                }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <div><button class="button" onclick={ctx.link().callback(|_| Msg::CheckUpdate)}>{"Check update"}</button></div>
+                        {
+                            if self.update_checked_json.as_ref() != Some(&self.json_object) {
+                                html! {}
+                            } else if self.update_violations.is_empty() {
+                                html! {<p class="passed-text">{"No incompatible edits ✓"}</p>}
+                            } else {
+                                html! {
+                                    <>
+                                    <h3>{"Incompatible edits:"}</h3>
+                                    <ul class="error-text">
+                                        { for self.update_violations.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                                    </ul>
+                                    </>
+                                }
+                            }
+                        }
+                        <div><button class="button" onclick={ctx.link().callback(|_| Msg::BuildUpdateTransition)}>{"Build and sign update"}</button></div>
+                        </>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            </>
+        }
+    }
+}
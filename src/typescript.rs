@@ -0,0 +1,133 @@
+//! TypeScript type definitions for the document types
+
+use serde_json::Value;
+
+use crate::{export::{pascal_case, required_names}, is_identifier};
+
+/// Constraint keywords listed in the doc comment of a property
+const CONSTRAINT_KEYWORDS: [&str; 11] = ["minLength", "maxLength", "pattern", "format", "minimum", "maximum", "minItems", "maxItems", "minProperties", "maxProperties", "contentMediaType"];
+
+const HEADER: &str = r#"// Generated by Data Contract Creator
+
+/** A 32-byte Dash Platform identifier, encoded as base58 */
+export type Identifier = string & { readonly __brand: "Identifier" };
+
+/** System fields present on every document */
+export interface DocumentMetadata {
+  $id: Identifier;
+  $ownerId: Identifier;
+  $dataContractId: Identifier;
+  $type: string;
+  $revision: number;
+  $createdAt?: number;
+  $updatedAt?: number;
+}
+"#;
+
+/// Generates a `.d.ts` file with one interface per document type
+pub fn generate(documents: &Value) -> String {
+    let mut interfaces = vec![HEADER.to_string()];
+    let mut names = vec![String::from("Identifier"), String::from("DocumentMetadata")];
+    if let Some(documents) = documents.as_object() {
+        for (name, schema) in documents {
+            write_interface(&pascal_case(name), schema, Some("DocumentMetadata"), &mut interfaces, &mut names);
+        }
+    }
+    interfaces.join("\n")
+}
+
+/// Writes the interface for an object schema after the interfaces of its nested objects. Returns the interface's
+/// name, which gets a numeric suffix when another interface already has it, as `a`+`bC` and `aB`+`c` would.
+fn write_interface(name: &str, schema: &Value, extends: Option<&str>, interfaces: &mut Vec<String>, names: &mut Vec<String>) -> String {
+    let name = unique_type_name(name, names);
+    let required = required_names(schema);
+    let mut out = doc_comment(schema, "");
+    match extends {
+        Some(base) => out.push_str(&format!("export interface {} extends {} {{\n", name, base)),
+        None => out.push_str(&format!("export interface {} {{\n", name)),
+    }
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (prop_name, property) in properties {
+            let ts_type = ts_type(property, &format!("{}{}", name, pascal_case(prop_name)), interfaces, names);
+            let optional = if required.contains(prop_name) { "" } else { "?" };
+            out.push_str(&doc_comment(property, "  "));
+            out.push_str(&format!("  {}{}: {};\n", property_key(prop_name), optional, ts_type));
+        }
+    }
+    out.push_str("}\n");
+    interfaces.push(out);
+    name
+}
+
+/// Adds a numeric suffix to a type name that is already taken, and records the result as taken
+fn unique_type_name(name: &str, names: &mut Vec<String>) -> String {
+    let name = if names.iter().any(|taken| taken == name) {
+        (2..).map(|n| format!("{}{}", name, n)).find(|candidate| !names.contains(candidate)).unwrap()
+    } else {
+        name.to_string()
+    };
+    names.push(name.clone());
+    name
+}
+
+/// TypeScript type for a property schema. Nested objects become interfaces named after `nested_name`.
+fn ts_type(property: &Value, nested_name: &str, interfaces: &mut Vec<String>, names: &mut Vec<String>) -> String {
+    if let Some(values) = property.get("enum").and_then(|e| e.as_array()) {
+        return values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" | ");
+    }
+    match property.get("type").and_then(|t| t.as_str()).unwrap_or_default() {
+        "string" => String::from("string"),
+        "integer" | "number" => String::from("number"),
+        "boolean" => String::from("boolean"),
+        "array" => {
+            if is_identifier(property) {
+                String::from("Identifier")
+            } else if property.get("byteArray").and_then(|b| b.as_bool()).unwrap_or(false) {
+                String::from("Uint8Array")
+            } else {
+                match property.get("items") {
+                    Some(items) => {
+                        let item_type = ts_type(items, &format!("{}Item", nested_name), interfaces, names);
+                        if item_type.contains(' ') { format!("({})[]", item_type) } else { format!("{}[]", item_type) }
+                    }
+                    None => String::from("unknown[]"),
+                }
+            }
+        }
+        "object" => write_interface(nested_name, property, None, interfaces, names),
+        _ => String::from("unknown"),
+    }
+}
+
+/// Quotes property names that are not valid TypeScript identifiers
+pub fn property_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid { name.to_string() } else { Value::String(name.to_string()).to_string() }
+}
+
+/// JSDoc comment with the description, `$comment` and constraints of a schema
+fn doc_comment(schema: &Value, indent: &str) -> String {
+    let mut lines = Vec::new();
+    for keyword in ["description", "$comment"] {
+        if let Some(text) = schema.get(keyword).and_then(|t| t.as_str()).filter(|t| !t.is_empty()) {
+            lines.extend(text.lines().map(|line| line.replace("*/", "*\\/")));
+        }
+    }
+    for keyword in CONSTRAINT_KEYWORDS {
+        if let Some(value) = schema.get(keyword) {
+            let value = value.as_str().map(String::from).unwrap_or_else(|| value.to_string());
+            lines.push(format!("@{} {}", keyword, value.replace("*/", "*\\/")));
+        }
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("{}/**\n", indent);
+    for line in lines {
+        out.push_str(&format!("{} * {}\n", indent, line));
+    }
+    out.push_str(&format!("{} */\n", indent));
+    out
+}
@@ -0,0 +1,212 @@
+//! Named projects autosaved to the browser's localStorage
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::HtmlSelectElement;
+use yew::{html, Event, Html, InputEvent, TargetCast};
+
+use crate::{diagram::Reference, DocumentType, Model, Msg, Property};
+
+/// Key of the project list
+const PROJECTS_KEY: &str = "data-contract-creator.projects";
+/// Key of the ID of the open project
+const CURRENT_PROJECT_KEY: &str = "data-contract-creator.current-project";
+
+/// A named contract in the workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Project {
+    pub id: u32,
+    pub name: String,
+}
+
+/// The part of `Model` that is saved per project
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+struct ProjectState {
+    document_types: Vec<DocumentType>,
+    /// Text of the JSON editor, which may hold an edit the form couldn't read yet
+    json_editor: String,
+    json_object: Vec<String>,
+    error_messages: Vec<String>,
+    validated_json: Option<Vec<String>>,
+    references: Vec<Reference>,
+}
+
+/// What `autosave` last wrote, so that updates that change nothing aren't written again
+#[derive(PartialEq)]
+pub struct SavedWorkspace {
+    projects: Vec<Project>,
+    current_project: u32,
+    state: ProjectState,
+}
+
+fn project_key(id: u32) -> String {
+    format!("data-contract-creator.project.{}", id)
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+pub fn read<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let value = storage()?.get_item(key).ok()??;
+    serde_json::from_str(&value).ok()
+}
+
+pub fn write<T: Serialize>(key: &str, value: &T) -> Result<(), String> {
+    let storage = storage().ok_or_else(|| String::from("Browser storage is not available, so changes are not saved"))?;
+    let value = serde_json::to_string(value).map_err(|e| format!("Could not save to browser storage: {}", e))?;
+    storage.set_item(key, &value).map_err(|_| String::from("Could not save to browser storage. It may be full"))
+}
+
+fn remove(key: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(key);
+    }
+}
+
+fn default_document_types() -> Vec<DocumentType> {
+    let mut document_type = DocumentType::default();
+    document_type.properties.push(Property::default());
+    vec![document_type]
+}
+
+impl Model {
+    /// Loads the project list and opens the last open project, creating a first project if there is none
+    pub fn open_workspace(&mut self) {
+        self.projects = read(PROJECTS_KEY).unwrap_or_default();
+        let current = read::<u32>(CURRENT_PROJECT_KEY).filter(|id| self.projects.iter().any(|p| p.id == *id));
+        match current.or_else(|| self.projects.first().map(|p| p.id)) {
+            Some(id) => self.load_project(id),
+            None => self.create_project(String::from("Untitled project"), default_document_types()),
+        }
+    }
+
+    /// Saves the open project if it changed since the last save. Called after every update.
+    pub fn autosave(&mut self) {
+        let saved = SavedWorkspace {
+            projects: self.projects.clone(),
+            current_project: self.current_project,
+            state: ProjectState {
+                document_types: self.document_types.clone(),
+                json_editor: self.json_editor.clone(),
+                json_object: self.json_object.clone(),
+                error_messages: self.error_messages.clone(),
+                validated_json: self.validated_json.clone(),
+                references: self.references.clone(),
+            },
+        };
+        if self.saved_workspace.as_ref() == Some(&saved) {
+            return;
+        }
+        let result = write(&project_key(saved.current_project), &saved.state)
+            .and_then(|_| write(PROJECTS_KEY, &saved.projects))
+            .and_then(|_| write(CURRENT_PROJECT_KEY, &saved.current_project));
+        self.workspace_messages = result.err().into_iter().collect();
+        // A failed write is tried again after the next update
+        self.saved_workspace = if self.workspace_messages.is_empty() { Some(saved) } else { None };
+    }
+
+    fn load_project(&mut self, id: u32) {
+        let state: ProjectState = read(&project_key(id)).unwrap_or_default();
+        self.document_types = if state.document_types.is_empty() { default_document_types() } else { state.document_types };
+        self.json_object = state.json_object;
+        self.error_messages = state.error_messages;
+        self.validated_json = state.validated_json;
+        self.references = state.references;
+        self.current_project = id;
+        self.project_name = self.projects.iter().find(|p| p.id == id).map(|p| p.name.clone()).unwrap_or_default();
+        self.restore_json_editor(state.json_editor);
+    }
+
+    /// Adds a project holding the given document types and opens it
+    pub fn create_project(&mut self, name: String, document_types: Vec<DocumentType>) {
+        let id = self.projects.iter().map(|p| p.id + 1).max().unwrap_or(1);
+        self.projects.push(Project { id, name: name.clone() });
+        self.current_project = id;
+        self.project_name = name;
+        self.document_types = document_types;
+        self.imported_json = String::new();
+        self.json_object = vec![];
+        self.error_messages = vec![];
+        self.validated_json = None;
+        self.references = vec![];
+        // The next sync fills the editor from the new document types
+        self.json_editor = String::new();
+        self.json_editor_synced = String::new();
+        self.json_editor_errors = vec![];
+    }
+
+    pub fn open_project(&mut self, id: u32) {
+        self.autosave();
+        self.load_project(id);
+    }
+
+    pub fn new_project(&mut self) {
+        self.autosave();
+        self.create_project(format!("Project {}", self.projects.len() + 1), default_document_types());
+    }
+
+    pub fn rename_project(&mut self) {
+        let name = self.project_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == self.current_project) {
+            project.name = name;
+        }
+    }
+
+    /// Copies the open project, including its JSON editor text, validation results and references, and opens the copy
+    pub fn duplicate_project(&mut self) {
+        self.autosave();
+        let name = self.projects.iter().find(|p| p.id == self.current_project).map(|p| format!("{} (copy)", p.name)).unwrap_or_default();
+        let (json_object, error_messages, validated_json, references) = (self.json_object.clone(), self.error_messages.clone(), self.validated_json.clone(), self.references.clone());
+        let json_editor = self.json_editor.clone();
+        self.create_project(name, self.document_types.clone());
+        self.json_object = json_object;
+        self.error_messages = error_messages;
+        self.validated_json = validated_json;
+        self.references = references;
+        self.restore_json_editor(json_editor);
+    }
+
+    /// Deletes the open project after confirmation and opens another one
+    pub fn delete_project(&mut self) {
+        let name = self.projects.iter().find(|p| p.id == self.current_project).map(|p| p.name.clone()).unwrap_or_default();
+        let confirmed = web_sys::window()
+            .and_then(|window| window.confirm_with_message(&format!("Delete the project \"{}\"?", name)).ok())
+            .unwrap_or(false);
+        if !confirmed {
+            return;
+        }
+        remove(&project_key(self.current_project));
+        self.projects.retain(|p| p.id != self.current_project);
+        match self.projects.first().map(|p| p.id) {
+            Some(id) => self.load_project(id),
+            None => self.create_project(String::from("Untitled project"), default_document_types()),
+        }
+    }
+
+    pub fn view_projects(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="input-fields">
+                <h2>{"Projects"}</h2>
+                <select onchange={ctx.link().callback(|e: Event| Msg::OpenProject(e.target_dyn_into::<HtmlSelectElement>().unwrap().value().parse().unwrap_or_default()))}>
+                    {for self.projects.iter().map(|project| html! {
+                        <option value={project.id.to_string()} selected={project.id == self.current_project}>{project.name.clone()}</option>
+                    })}
+                </select>
+                <input type="text" placeholder="Project name" value={self.project_name.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateProjectName(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <div>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::NewProject)}>{"New"}</button>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::RenameProject)}>{"Rename"}</button>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::DuplicateProject)}>{"Duplicate"}</button>
+                    <button class="button2" onclick={ctx.link().callback(|_| Msg::DeleteProject)}>{"Delete"}</button>
+                </div>
+                <ul class="error-text">
+                    { for self.workspace_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                </ul>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! YAML import and export of the document schemas
+
+use serde_json::{Map, Value};
+use yew::{html, Html, InputEvent, TargetCast};
+
+use crate::{Model, Msg};
+
+/// Comments written on or above a mapping key, with the keys leading to it.
+/// Comments inside sequences have an empty path since they can't belong to a schema.
+struct KeyComment {
+    path: Vec<String>,
+    line: usize,
+    text: String,
+}
+
+/// Splits a YAML line into its key and the rest, if it is a `key: value` or `key:` line
+fn split_key(line: &str) -> Option<(String, &str)> {
+    let (key, rest) = if line.starts_with('"') || line.starts_with('\'') {
+        let quote = line.chars().next().unwrap_or('"');
+        let end = line[1..].find(quote)? + 1;
+        (line[1..end].to_string(), &line[end + 1..])
+    } else {
+        let end = line.find(": ").or_else(|| line.strip_suffix(':').map(|l| l.len()))?;
+        (line[..end].trim_end().to_string(), &line[end..])
+    };
+    let rest = rest.strip_prefix(':')?;
+    if rest.is_empty() || rest.starts_with(' ') { Some((key, rest.trim_start())) } else { None }
+}
+
+/// Finds a `# comment` after a value, ignoring `#` inside quoted strings
+fn trailing_comment(value: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && (i == 0 || previous == ' ') => return Some(value[i + 1..].trim()),
+            None => {}
+        }
+        previous = c;
+    }
+    None
+}
+
+/// Collects the comments of block mapping keys and sequence items. Lines inside block scalars are skipped.
+fn key_comments(input: &str) -> Vec<KeyComment> {
+    let mut comments = Vec::new();
+    // Indentation and key of each enclosing mapping key; sequence items are recorded as "-"
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut block_scalar_indent: Option<usize> = None;
+    for (i, line) in input.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if let Some(block_indent) = block_scalar_indent {
+            if trimmed.is_empty() || indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if trimmed.is_empty() || trimmed == "---" {
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            pending.push(comment.trim().to_string());
+            continue;
+        }
+        while stack.last().map_or(false, |(top, _)| *top >= indent) {
+            stack.pop();
+        }
+        let mut line = trimmed;
+        if line == "-" || line.starts_with("- ") {
+            stack.push((indent, String::from("-")));
+            line = line[1..].trim_start();
+        }
+        let mut value = line;
+        if let Some((key, rest)) = split_key(line) {
+            stack.push((indent + trimmed.len() - line.len(), key));
+            if rest.starts_with('|') || rest.starts_with('>') {
+                block_scalar_indent = Some(indent);
+            }
+            value = rest;
+        }
+        pending.extend(trailing_comment(value).map(String::from));
+        if !pending.is_empty() {
+            let in_sequence = stack.iter().any(|(_, key)| key == "-");
+            let path = if in_sequence { vec![] } else { stack.iter().map(|(_, key)| key.clone()).collect() };
+            comments.push(KeyComment { path, line: i + 1, text: pending.join(" ") });
+        }
+        pending.clear();
+    }
+    comments
+}
+
+/// Whether a path leads to a schema position: a document type, followed by any number of
+/// `properties`/<name> and `items` steps
+fn is_schema_path(root: &Value, path: &[String]) -> bool {
+    let path = match ["documents", "documentSchemas"].into_iter().find(|key| root.get(key).is_some()) {
+        Some(key) if path.first().map(String::as_str) == Some(key) => &path[1..],
+        Some(_) => return false,
+        None => path,
+    };
+    // An empty path never matches: the root is the contract or the documents map, not a schema
+    let mut rest = match path.split_first() {
+        Some((_, rest)) => rest,
+        None => return false,
+    };
+    while let Some((key, tail)) = rest.split_first() {
+        rest = match key.as_str() {
+            "properties" if !tail.is_empty() => &tail[1..],
+            "items" => tail,
+            _ => return false,
+        };
+    }
+    true
+}
+
+/// Adds a comment to the `$comment` of the innermost schema (an object with a string `type` at a schema position) along the path
+fn attach_comment(root: &mut Value, path: &[String], text: &str) -> bool {
+    for len in (1..=path.len()).rev() {
+        let is_schema = is_schema_path(root, &path[..len])
+            && path[..len].iter().try_fold(&*root, |value, key| value.get(key)).map_or(false, |value| value.get("type").map_or(false, Value::is_string));
+        if is_schema {
+            let schema = path[..len].iter().fold(&mut *root, |value, key| &mut value[key.as_str()]);
+            let comment = match schema.get("$comment").and_then(|c| c.as_str()) {
+                Some(existing) if !existing.is_empty() => format!("{} {}", existing, text),
+                _ => text.to_string(),
+            };
+            schema["$comment"] = Value::String(comment);
+            return true;
+        }
+    }
+    false
+}
+
+/// Parses YAML document schemas, turning comments on document types and properties into `$comment`.
+/// Accepts the documents on their own or a whole contract with a `documents` field.
+pub fn import(input: &str) -> Result<(Map<String, Value>, Vec<String>), String> {
+    let mut root: Value = serde_yaml::from_str(input).map_err(|e| format!("Invalid YAML: {}", e))?;
+    let mut messages = Vec::new();
+    for comment in key_comments(input) {
+        if !attach_comment(&mut root, &comment.path, &comment.text) {
+            messages.push(format!("Line {}: the comment \"{}\" is not on a document type or property and was dropped", comment.line, comment.text));
+        }
+    }
+    let documents = match root.get("documents").or_else(|| root.get("documentSchemas")) {
+        Some(documents) => documents.clone(),
+        None => root,
+    };
+    match documents {
+        Value::Object(documents) => Ok((documents, messages)),
+        _ => Err(String::from("The YAML must be a mapping of document types")),
+    }
+}
+
+/// Generates YAML with the same content as the JSON contract. `$comment` stays a key so that it round-trips.
+pub fn export(documents: &Value) -> String {
+    serde_yaml::to_string(documents).unwrap_or_else(|e| format!("# Could not export YAML: {}", e))
+}
+
+impl Model {
+    pub fn import_yaml(&mut self) {
+        match import(&self.yaml_input) {
+            Ok((documents, messages)) => {
+                self.imported_json = Value::Object(documents).to_string();
+                self.yaml_messages = match self.parse_imported_json() {
+                    Ok(dropped) => messages.into_iter().chain(dropped).collect(),
+                    Err(errors) => errors,
+                };
+            }
+            Err(message) => {
+                self.yaml_messages = vec![message];
+            }
+        }
+    }
+
+    pub fn view_yaml_import(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <p class="output-container">
+                <h2>{"Import YAML"}</h2>
+                <textarea class="textarea" placeholder="Paste the document types as YAML" value={self.yaml_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateYamlInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div><button class="button" onclick={ctx.link().callback(|_| Msg::ImportYaml)}>{"Import YAML"}</button></div>
+                {
+                    if self.yaml_messages.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <>
+                            <h3>{"Import notes"}</h3>
+                            <ul class="error-text">
+                                { for self.yaml_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                            </ul>
+                            </>
+                        }
+                    }
+                }
+            </p>
+        }
+    }
+}